@@ -8,6 +8,7 @@ use ::core::convert::{From, Into, TryFrom, TryInto};
 pub struct Small<T>(pub T);
 
 impl<T: Into<u8>> From<Small<T>> for u8 {
+    #[inline]
     fn from(value: Small<T>) -> u8 {
         value.0.into()
     }
@@ -19,6 +20,7 @@ where
 {
     type Error = <u8 as TryInto<T>>::Error;
 
+    #[inline]
     fn try_from(value: u8) -> ::core::result::Result<Small<T>, Self::Error> {
         value.try_into().map(Small)
     }
@@ -49,8 +51,8 @@ crate::register! {
 }
 
 crate::periph! {
-    /// The peripheral
-    ComplexPeripheral;
+    /// The peripheral, with some reserved space after the last register
+    ComplexPeripheral: 0x08;
     /// A `SmallRegister`
     rw SMALL1 @ 0x00: u8 = SmallRegister;
     /// An other `SmallRegister`
@@ -5,10 +5,10 @@
 crate::device! {
     /// The microcontroller
     Microcontroller;
-    /// Some peripheral
-    PERIPH1 @ 0x0010: super::periph::Peripheral;
-    /// An other instance of the same peripheral
-    PERIPH2 @ 0x0020: super::periph::Peripheral;
+    /// Some peripheral, whose registers can be awaited
+    PERIPH1 @ 0x0010: super::periph::Peripheral { waker };
+    /// An other instance of the same peripheral, with atomic set, clear and toggle aliases
+    PERIPH2 @ 0x0020: super::periph::Peripheral { set: 0x2000, clear: 0x3000, toggle: 0x1000 };
     /// An other peripheral
     COMPLEX @ 0x0030: super::complex::ComplexPeripheral;
 }
@@ -17,3 +17,9 @@ crate::device! {
 fn zero_sized() {
     ::core::assert_eq!(::core::mem::size_of::<Microcontroller>(), 0);
 }
+
+#[test]
+fn take() {
+    ::core::assert!(Microcontroller::take().is_some());
+    ::core::assert!(Microcontroller::take().is_none());
+}
@@ -3,9 +3,10 @@
 //! The macro invocations can be seen in the source code. This module exists only in documentation
 //! and tests
 //!
-//! To actually use the generated device struct, it must be cased from `()`. This should idealy be
-//! done in the runtime support crate (`-rt` crate), or at the very beginning of your main function.
-//! There sould be only one instance of the device (and the right one) in your whole program.
+//! To actually use the generated device struct, it must be obtained with its `take` method. This
+//! should idealy be done in the runtime support crate (`-rt` crate), or at the very beginning of
+//! your main function. There sould be only one instance of the device (and the right one) in your
+//! whole program.
 //!
 //! ```
 //! // In some library
@@ -15,7 +16,10 @@
 //! }
 //!
 //! // In a `-rt` crate or at the very beginning of your program:
-//! let peripherals: YourDevice = unsafe { core::mem::transmute(()) };
+//! let peripherals = YourDevice::take().unwrap();
+//!
+//! // Further calls return `None`
+//! assert!(YourDevice::take().is_none());
 //! ```
 
 // To check that everithing is explicit in the macro
@@ -45,6 +45,7 @@ mod tests {
     use crate::utils::{Peripheral, *};
     use ::core::assert_eq;
 
+    #[allow(clippy::upper_case_acronyms)]
     enum PERIPH {}
     impl Peripheral for PERIPH {
         const BASE: usize = 0;
@@ -22,9 +22,26 @@
 //! registers and peripherals. These types can be seen in the [`example`] module.
 //!
 //! Registers are accessed with the [`Reg`] struct. [`Value`]s are used to read and write them.
+//! Registers of external devices, e.g. behind an I2C or SPI bus, are accessed through a [`Bus`].
+//! Data in normal memory with the same layout as a register, e.g. DMA descriptors, is stored in a
+//! [`Stored`] value.
 //!
-//! To use the generated device struct, create it from `()` as part of your initialisation routine.
-//! There sould be only one instance of the device (and the right one) in your whole program.
+//! To use the generated device struct, obtain it with its `take` method as part of your
+//! initialisation routine. There sould be only one instance of the device (and the right one) in
+//! your whole program.
+//!
+//! ## Cargo features
+//!
+//! - `critical-section`: allows to modify registers from a shared reference with
+//!   [`Reg::modify_cs`] and similar methods, using the [`critical-section`] crate. This is also
+//!   used by devices' `take` method on targets without atomic swap.
+//! - `mock`: adds the [`mock`] module, to test register accesses on the host. This requires `std`.
+//! - `serde`: implements `Serialize` and `Deserialize` for [`Value`] and [`FieldValues`], as
+//!   raw integers or, in human-readable formats, as maps of field names to field values.
+//! - `std`: adds the [`mmap`] module, to access peripherals from Linux userspace through
+//!   `/dev/mem`, UIO or PCI resource files.
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
 //!
 //! ## Operators
 //!
@@ -61,6 +78,9 @@
 #![warn(clippy::missing_inline_in_public_items)]
 #![warn(clippy::missing_const_for_fn)]
 
+#[cfg(any(test, feature = "mock", feature = "std"))]
+extern crate std;
+
 #[doc(hidden)]
 pub use paste::paste;
 pub use utils::*;
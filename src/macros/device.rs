@@ -34,19 +34,149 @@
 ///   peripheral. That is, each register will be at (base address + register offset).
 /// - The peripheral type is a struct defined with the [`periph!`] macro. You can either import
 ///   each used peripheral or use absolute or relative paths.
-
+///
+/// The device struct is obtained with `take`, which returns `Some` only the first time it is
+/// called. This ensures there is a single instance of the device in the whole program. On targets
+/// without atomic swap, `take` isn't atomic with respect to interrupts unless the
+/// `critical-section` feature is enabled, and should thus be called before interrupts are enabled.
+///
+/// ```
+/// # peripherals::periph! {
+/// #   MyPeripheral;
+/// #   rw MY_REG @ 0: u16 = 0 {}
+/// # }
+/// peripherals::device!{
+///     MyMicrocontroller;
+///     PERIPH @ 0x1234: MyPeripheral;
+/// }
+///
+/// let device = MyMicrocontroller::take().unwrap();
+/// assert!(MyMicrocontroller::take().is_none());
+/// ```
+///
+/// Some microcontrollers have alias address ranges to atomically set, clear or toggle bits of
+/// registers. The offsets of these aliases (from the address of each register) can be given after
+/// the peripheral type. This implements the [`AtomicAlias`](crate::AtomicAlias) trait, allowing
+/// to use [`Reg::set_bits`](crate::Reg::set_bits) and similar methods.
+///
+/// ```
+/// # peripherals::periph! {
+/// #   MyPeripheral;
+/// #   rw MY_REG @ 0: u16 = 0 {}
+/// # }
+/// peripherals::device!{
+///     MyMicrocontroller;
+///     PERIPH @ 0x4001_4000: MyPeripheral { set: 0x2000, clear: 0x3000, toggle: 0x1000 };
+/// }
+/// ```
+///
+/// On Cortex-M3 and Cortex-M4, peripherals in the bit-band region can be declared with the
+/// `bit_band` option. This implements the [`BitBand`](crate::BitBand) trait, allowing to use
+/// [`Reg::modify_bit_band`](crate::Reg::modify_bit_band). The macro checks that the peripheral is
+/// in the bit-band region.
+///
+/// ```
+/// # peripherals::periph! {
+/// #   MyPeripheral;
+/// #   rw MY_REG @ 0: u16 = 0 {}
+/// # }
+/// peripherals::device!{
+///     MyMicrocontroller;
+///     PERIPH @ 0x4001_0000: MyPeripheral { bit_band };
+/// }
+/// ```
+///
+/// Registers can be awaited with [`Reg::wait_for`](crate::Reg::wait_for) for peripherals declared
+/// with the `poll` or `waker` option. This implements the
+/// [`WakerRegistry`](crate::WakerRegistry) trait. With `poll`, the register is read each time the
+/// executor polls the task, which is woken immediately. With `waker`, the task is woken by calling
+/// `wake` on the [`AtomicWaker`](crate::AtomicWaker) returned by the `waker` function of the
+/// peripheral instance, typically from its interrupt handler.
+///
+/// ```
+/// # peripherals::periph! {
+/// #   MyPeripheral;
+/// #   rw MY_REG @ 0: u16 = 0 {}
+/// # }
+/// peripherals::device!{
+///     MyMicrocontroller;
+///     PERIPH1 @ 0x1234: MyPeripheral { poll };
+///     PERIPH2 @ 0x1240: MyPeripheral { waker };
+/// }
+///
+/// // In the interrupt handler of `PERIPH2`
+/// PERIPH2::waker().wake();
+/// ```
+///
+/// The macro checks at compile time that each base address is suitably aligned for the registers
+/// of the peripheral, and that peripheral instances don't overlap. The size of each peripheral is
+/// given by the [`RegisterBlock`](crate::RegisterBlock) trait.
+///
+/// ```compile_fail
+/// # peripherals::periph! {
+/// #   MyPeripheral;
+/// #   rw MY_REG @ 0: u16 = 0 {}
+/// # }
+/// peripherals::device!{
+///     MyMicrocontroller;
+///     PERIPH1 @ 0x1234: MyPeripheral;
+///     PERIPH2 @ 0x1235: MyPeripheral; // Overlaps with `PERIPH1`, and is misaligned
+/// }
+/// ```
 #[macro_export]
 macro_rules! device {
     (
         $(#[$($device_attr:tt)*])*
         $device:ident;
         $($(#[$($periph_attr:tt)*])*
-        $periph:ident @ $base:literal : $type:ty;)*
+        $periph:ident @ $base:literal : $type:ty $({ $($option:tt)* })?;)*
     ) => {
         $crate::periph_attr_inner! { @type { $([$($device_attr)*])* } {} {
             device_inner: @struct $device {$( $(#[$($periph_attr)*])* $periph $type; )*} {}
         }}
 
+        $crate::periph_attr_inner! { @impl { $([$($device_attr)*])* } {} {
+        periph_attr_inner: @expand
+            impl $device {
+                /// Take the device
+                ///
+                /// This returns `Some` only the first time it is called (including calls to
+                /// `steal`), and `None` afterwards.
+                #[inline]
+                pub fn take() -> ::core::option::Option<$device> {
+                    if $device::taken().take() {
+                        ::core::option::Option::Some(unsafe { $device::steal() })
+                    } else {
+                        ::core::option::Option::None
+                    }
+                }
+
+                /// Unconditionally create the device
+                ///
+                /// Further calls to `take` will return `None`.
+                ///
+                /// # Safety
+                ///
+                /// There must be only one instance of the device in the whole program. Creating
+                /// more instances allows to modify the same registers from different places.
+                #[inline]
+                pub unsafe fn steal() -> $device {
+                    $device::taken().set();
+                    ::core::mem::transmute(())
+                }
+
+                #[inline]
+                fn taken() -> &'static $crate::Taken {
+                    static TAKEN: $crate::Taken = $crate::Taken::new();
+                    &TAKEN
+                }
+            }
+        }}
+
+        $crate::periph_attr_inner! { @impl { $([$($device_attr)*])* } {} {
+            device_inner: @check {$( $(#[$($periph_attr)*])* $periph @ $base: $type; )*} {}
+        }}
+
         $($crate::periph_attr_inner! { @type { $([$($periph_attr)*])* } {} {
         periph_attr_inner: @expand
             #[derive(Debug)]
@@ -60,6 +190,10 @@ macro_rules! device {
                 const NAME: &'static str = stringify!($periph);
             }
         }})*
+
+        $($crate::periph_attr_inner! { @impl { $([$($periph_attr)*])* } {} {
+            device_inner: @option $periph $type { $($($option)*)? }
+        }})*
     }
 }
 
@@ -88,4 +222,85 @@ macro_rules! device_inner {
             device_inner: @struct $device { $($rest)* } { $($parsed)* $(#[$prev])* $periph $type; }
         }}
     };
+
+    (@option $(#[$attr:meta])* $periph:ident $type:ty {}) => {};
+    (@option $(#[$attr:meta])* $periph:ident $type:ty {
+        set: $set:literal, clear: $clear:literal, toggle: $toggle:literal $(, $($rest:tt)*)?
+    }) => {
+        $(#[$attr])*
+        impl $crate::AtomicAlias for $periph {
+            const SET: usize = $set;
+            const CLEAR: usize = $clear;
+            const TOGGLE: usize = $toggle;
+        }
+        $crate::device_inner!(@option $(#[$attr])* $periph $type { $($($rest)*)? });
+    };
+    (@option $(#[$attr:meta])* $periph:ident $type:ty { bit_band $(, $($rest:tt)*)? }) => {
+        $(#[$attr])*
+        impl $crate::BitBand for $periph {}
+
+        $crate::paste! {
+            $(#[$attr])*
+            const _: () = $crate::check_bit_band(
+                <$periph as $crate::Peripheral>::BASE,
+                <$type<$periph> as $crate::RegisterBlock>::SIZE,
+            );
+        }
+        $crate::device_inner!(@option $(#[$attr])* $periph $type { $($($rest)*)? });
+    };
+
+    (@option $(#[$attr:meta])* $periph:ident $type:ty { poll $(, $($rest:tt)*)? }) => {
+        $(#[$attr])*
+        impl $crate::WakerRegistry for $periph {}
+        $crate::device_inner!(@option $(#[$attr])* $periph $type { $($($rest)*)? });
+    };
+    (@option $(#[$attr:meta])* $periph:ident $type:ty { waker $(, $($rest:tt)*)? }) => {
+        $(#[$attr])*
+        impl $crate::WakerRegistry for $periph {
+            #[inline]
+            fn register(waker: &::core::task::Waker) {
+                $periph::waker().register(waker);
+            }
+        }
+
+        $(#[$attr])*
+        impl $periph {
+            /// The waker of the task waiting on a register of this peripheral
+            ///
+            /// This should be woken by the interrupt handler of the peripheral.
+            #[inline]
+            pub fn waker() -> &'static $crate::AtomicWaker {
+                static WAKER: $crate::AtomicWaker = $crate::AtomicWaker::new();
+                &WAKER
+            }
+        }
+        $crate::device_inner!(@option $(#[$attr])* $periph $type { $($($rest)*)? });
+    };
+
+    (@check {} {$(#[$device_attr:meta])*
+        $($periph:ident @ $base:literal: $type:ty; $(#[$attr:meta])*)*
+    }) => { $crate::paste! {
+        $(#[$device_attr])*
+        #[allow(unused_doc_comments)]
+        const _: () = $crate::check_instances(&[$(
+            $(#[$attr])*
+            (
+                $base,
+                <$type<$periph> as $crate::RegisterBlock>::SIZE,
+                <$type<$periph> as $crate::RegisterBlock>::ALIGN,
+            ),
+        )*]);
+    }};
+    (@check $(#[$attr:meta])* {} {$($rest:tt)*} ) => {
+        $crate::device_inner!(@check {} { $($rest)* $(#[$attr])* } );
+    };
+    (@check
+        $(#[$prev:meta])*
+        { $(#[$($attr:tt)*])* $periph:ident @ $base:literal: $type:ty; $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+            device_inner: @check { $($rest)* } { $($parsed)* $(#[$prev])* $periph @ $base: $type; }
+        }}
+    };
 }
@@ -62,7 +62,6 @@
 /// assert_eq!(!Status(true), Status(false));
 /// assert_eq!(!Status(false), Status(true));
 /// ```
-
 #[macro_export]
 macro_rules! field_type {
     ($(#[$($attr:tt)*])* enum $name:ident $int:tt {
@@ -38,6 +38,261 @@
 /// - The reset value (here `0x1234`) is the "default" value of the register, i.e. the one after a
 ///   reset of the microcontroller.
 ///
+/// The generated peripheral struct has one public field per register. It can be destructured to
+/// give each register to a different driver or interrupt handler. Peripherals and registers can
+/// also be created with their unsafe `steal` function.
+///
+/// ```no_run
+/// # peripherals::periph!{
+/// #     MyPeripheral;
+/// #     rw CONFIG @ 0x00: u16 = 0x1234 {}
+/// #     r STATUS @ 0x02: u16 = 0x0000 {}
+/// # }
+/// # peripherals::device!{
+/// #     MyMicrocontroller;
+/// #     PERIPH @ 0x1234: MyPeripheral;
+/// # }
+/// use peripherals::Reg;
+///
+/// let MyMicrocontroller { periph } = MyMicrocontroller::take().unwrap();
+/// let MyPeripheral { config, status } = periph;
+///
+/// // `config` and `status` can now be moved to different drivers
+/// let config: Reg<CONFIG, PERIPH> = config;
+/// let status: Reg<STATUS, PERIPH> = status;
+///
+/// // Or, if you know no one else uses this register:
+/// let status = unsafe { Reg::<STATUS, PERIPH>::steal() };
+/// ```
+///
+/// The macro also generates a snapshot struct, named after the peripheral (here
+/// `MyPeripheralSnapshot`), with the [`Value`](crate::Value) of each readable register. It is
+/// created by the `snapshot` method of the peripheral, and written back by `restore`. Its `diff`
+/// method lists the [`FieldChange`](crate::FieldChange)s between two snapshots.
+///
+/// ```no_run
+/// # peripherals::periph!{
+/// #     MyPeripheral;
+/// #     rw CONFIG @ 0x00: u16 = 0x1234 {
+/// #         MODE: 0..1 = struct Mode(u8);
+/// #     }
+/// #     r STATUS @ 0x02: u16 = 0x0000 {}
+/// # }
+/// # peripherals::device!{
+/// #     MyMicrocontroller;
+/// #     PERIPH @ 0x1234: MyPeripheral;
+/// # }
+/// let mut periph = MyMicrocontroller::take().unwrap().periph;
+///
+/// let saved = periph.snapshot();
+/// // Enter a low-power mode...
+/// for change in saved.diff(&periph.snapshot()) {
+///     println!("{}.{}: {} -> {}", change.register, change.field, change.old, change.new);
+/// }
+/// periph.restore(&saved); // Writes CONFIG
+/// periph.restore_config(&saved); // Writes CONFIG alone
+/// ```
+///
+/// Registers without fields are listed as a whole by `diff`, with an empty field name.
+///
+/// The `Dyn` peripheral struct (here `DynMyPeripheral`) is also created from a base address only
+/// known at runtime with its unsafe `from_base` function, which checks the alignment of the
+/// address. The lifetime of the returned reference should be tied to the memory mapping.
+///
+/// ```
+/// # peripherals::periph!{
+/// #     MyPeripheral;
+/// #     rw CONFIG @ 0x00: u16 = 0x1234 {
+/// #         MODE: 0..1 = struct Mode(u8);
+/// #     }
+/// #     r STATUS @ 0x02: u16 = 0x0000 {}
+/// # }
+/// let mut memory = [0u16; 2];
+/// let periph = unsafe { DynMyPeripheral::from_base(memory.as_mut_ptr() as usize) };
+/// periph.config.modify(Mode(2));
+/// assert_eq!(memory[0], 0x0002);
+/// ```
+///
+/// The size of the register block is the end of the last register. A larger size can be given
+/// after the peripheral name, e.g. if the peripheral has reserved space after its last register.
+/// It is used by the [`device!`] macro to check that peripheral instances don't overlap. Registers
+/// are checked to fit in the declared size, and to be aligned to their access width.
+///
+/// ```
+/// use peripherals::RegisterBlock;
+///
+/// peripherals::periph!{
+///     // name         size
+///     MyPeripheral : 0x100;
+///     rw MY_REG @ 0x00: u16 = 0x1234 {}
+/// }
+///
+/// assert_eq!(DynMyPeripheral::SIZE, 0x100);
+/// assert_eq!(DynMyPeripheral::ALIGN, 2);
+/// ```
+///
+/// ```compile_fail
+/// peripherals::periph!{
+///     MyPeripheral : 0x02;
+///     rw MY_REG @ 0x02: u16 = 0x1234 {}
+/// }
+/// ```
+///
+/// ```compile_fail
+/// peripherals::periph!{
+///     MyPeripheral;
+///     rw MY_REG @ 0x01: u32 = 0x1234 {}
+/// }
+/// ```
+///
+/// Registers of external devices accessed through a [`Bus`](crate::Bus) are not accessed in
+/// memory, and are often at consecutive addresses. Such peripherals are declared with `bus` after
+/// the peripheral name and size. Their registers are not required to be aligned, but the
+/// peripheral can't be used with the [`device!`] macro or created with `from_base`. The registers
+/// are transferred with their own width and the byte order of the bus, so they can't have another
+/// access width or byte order.
+///
+/// ```
+/// use peripherals::RegisterBlock;
+///
+/// peripherals::periph!{
+///     MySensor bus;
+///     rw CONFIG @ 0x00: u16 = 0x0000 {}
+///     r SHUNT @ 0x01: u16 = 0x0000 {}
+/// }
+///
+/// assert_eq!(DynMySensor::SIZE, 3);
+/// ```
+///
+/// ```compile_fail
+/// peripherals::periph!{
+///     MySensor bus;
+///     rw CONFIG @ 0x00: u16 = 0x0000 {}
+/// }
+///
+/// let sensor = unsafe { DynMySensor::from_base(0x4000_0000) };
+/// ```
+///
+/// ```compile_fail
+/// peripherals::periph!{
+///     MySensor bus;
+///     rw CONFIG @ 0x00 big_endian: u16 = 0x0000 {}
+/// }
+/// ```
+///
+/// Registers are in the native byte order by default. Peripherals with registers in another byte
+/// order are declared with `big_endian` or `little_endian` after the peripheral name and size.
+/// This can be overriden for a single register after its offset. The [`Value`](crate::Value) of
+/// the register always holds the logical value, and bytes are swapped when accessing the register.
+///
+/// ```
+/// peripherals::periph!{
+///     MyPeripheral: 0x10 big_endian;
+///     rw CONFIG @ 0x00: u32 = 0x0000 {
+///         MODE: 0..7 = struct Mode(u8);
+///     }
+///     rw NATIVE @ 0x04 little_endian: u32 = 0x0000 {
+///         MODE: 0..7 = struct NativeMode(u8);
+///     }
+/// }
+///
+/// let mut memory = [0u32; 4];
+/// let periph = unsafe { DynMyPeripheral::from_base(memory.as_mut_ptr() as usize) };
+/// periph.config.modify(Mode(0x12));
+/// periph.native.modify(NativeMode(0x34));
+/// assert_eq!(periph.config.field(CONFIG::MODE), Mode(0x12));
+/// assert_eq!(memory[0].to_ne_bytes(), [0x00, 0x00, 0x00, 0x12]);
+/// assert_eq!(memory[1].to_ne_bytes(), [0x34, 0x00, 0x00, 0x00]);
+/// ```
+///
+/// Registers are accessed with their own width by default. Another access width can be given with
+/// `as` and `u8`, `u16`, `u32` or `u64` after the register width. Wider accesses are used e.g. for
+/// 8-bit registers that only support 32-bit accesses, the register being in the least significant
+/// bits. Narrower accesses split the register in several accesses at consecutive addresses, in the
+/// order given after the access width: `low_first` (the default) or `high_first`.
+///
+/// ```
+/// use peripherals::{RegisterBlock, Value};
+///
+/// peripherals::periph!{
+///     MyPeripheral;
+///     rw CONTROL @ 0x00: u8 as u32 = 0x00 {
+///         ENABLE: 0 = struct Enable(bool);
+///     }
+///     rw COUNTER @ 0x08: u64 as u32 high_first = 0x0000 {
+///         LOW: 0..31 = struct Low(u32);
+///         HIGH: 32..63 = struct High(u32);
+///     }
+/// }
+///
+/// assert_eq!(DynMyPeripheral::SIZE, 0x10);
+/// assert_eq!(DynMyPeripheral::ALIGN, 4);
+///
+/// let mut memory = [0u32; 4];
+/// let periph = unsafe { DynMyPeripheral::from_base(memory.as_mut_ptr() as usize) };
+/// periph.control.modify(Enable(true)); // Writes 0x0000_0001 to 0x00
+/// // On little-endian targets, writes 0x0123_4567 to 0x0c, then 0x89ab_cdef to 0x08
+/// periph.counter.write(Value::reset() | Low(0x89ab_cdef) | High(0x0123_4567));
+/// assert_eq!(memory[0], 0x0000_0001);
+/// assert_eq!(periph.counter.read().value(), 0x0123_4567_89ab_cdef);
+/// ```
+///
+/// ```compile_fail
+/// peripherals::periph!{
+///     MyPeripheral;
+///     rw CONTROL @ 0x00: u8 as bool = 0x00 {}
+/// }
+/// ```
+///
+/// Data registers accessed repeatedly at the same address, e.g. to push to or pop from a FIFO, are
+/// declared with `fifo` after their offset. They implement [`FifoRegister`](crate::FifoRegister),
+/// which provides bulk methods such as [`Reg::write_iter`](crate::Reg::write_iter) and
+/// [`Reg::read_into`](crate::Reg::read_into). They are not part of snapshots, as reading them
+/// usually pops data.
+///
+/// ```
+/// peripherals::periph!{
+///     MyPeripheral;
+///     rw DATA @ 0x00 fifo: u32 = 0x0000 {
+///         BYTE: 0..7 = struct Byte(u8);
+///     }
+/// }
+///
+/// let mut memory = [0u32; 1];
+/// let periph = unsafe { DynMyPeripheral::from_base(memory.as_mut_ptr() as usize) };
+/// periph.data.write_iter([Byte(1), Byte(2), Byte(3)].iter().copied());
+/// assert_eq!(memory[0], 3);
+///
+/// let mut buffer = [Byte(0); 2];
+/// periph.data.read_into(DATA::BYTE, &mut buffer);
+/// assert_eq!(buffer, [Byte(3), Byte(3)]);
+/// ```
+///
+/// Memory regions embedded in the peripheral, e.g. packet buffers or look-up tables, are declared
+/// with their number of elements in brackets after the element width. The elements are described
+/// like registers, and the region is accessed through a [`Memory`](crate::Memory), with
+/// bounds-checked indexes. Memory regions are not part of snapshots.
+///
+/// ```
+/// use peripherals::Value;
+///
+/// peripherals::periph!{
+///     MyPeripheral;
+///     rw CONTROL @ 0x00: u32 = 0x0000 {}
+///     rw LUT @ 0x10: u16[4] = 0x0000 {
+///         GAIN: 0..7 = struct Gain(u8);
+///     }
+/// }
+///
+/// let mut memory = [0u32; 6];
+/// let periph = unsafe { DynMyPeripheral::from_base(memory.as_mut_ptr() as usize) };
+/// periph.lut.copy_from_slice(1, &[Value::reset() | Gain(1), Value::reset() | Gain(2)]);
+/// periph.lut.modify(3, Gain(3));
+/// assert_eq!(periph.lut.read(2).field(LUT::GAIN), Gain(2));
+/// assert_eq!(periph.lut.get(4), None);
+/// assert_eq!(periph.lut.len(), 4);
+/// ```
+///
 /// The field description is the same a for the [`register!`] macro, but leading `+` are not needed.
 ///
 /// ```
@@ -59,43 +314,122 @@
 /// #    }
 /// # }
 /// ```
-
 #[macro_export]
 macro_rules! periph {
     (
         $(#[$($periph_attr:tt)*])*
-        $periph:ident;
-        $($(#[$($reg_attr:tt)*])* $rw:ident $reg:ident @ $offset:literal : $int:ty = $desc1:tt $desc2:tt)*
+        $periph:ident $(: $size:literal)? $endian:ident $mode:ident;
+        $($(#[$($reg_attr:tt)*])*
+            $rw:ident $reg:ident @ $offset:literal $($reg_option:ident)* : $int:ty
+            $([$len:literal])? $(as $access:ident $($order:ident)?)? = $desc1:tt $desc2:tt
+        )*
     ) => {
         $crate::periph_attr_inner! { @type { $([$($periph_attr)*])* } {} {
-            periph_inner: @struct $periph {$( $(#[$($reg_attr)*])* $reg )*} {}
+            periph_inner: @struct $periph {$( $(#[$($reg_attr)*])* $reg [$($len)?] )*} {}
         }}
 
         $crate::paste! { $crate::periph_attr_inner! { @impl { $([$($periph_attr)*])* } {} {
         periph_attr_inner: @expand
             impl<P: $crate::Peripheral> $periph<P> {
-                /// Erase peripheral information
+                /// Unconditionally create the peripheral
                 ///
-                /// This allows to choose at runtime which instance of a peripheral to use.
+                /// The peripheral is normally obtained from the device struct generated by the
+                /// `device!` macro.
+                ///
+                /// # Safety
+                ///
+                /// There must be only one instance of each peripheral in the whole program.
+                /// Creating more instances allows to modify the same registers from different
+                /// places.
                 #[inline]
-                pub fn into_dyn(self) -> &'static mut [<Dyn $periph>] {
-                    unsafe { &mut *(P::BASE as *mut _) }
+                pub unsafe fn steal() -> $periph<P> {
+                    ::core::mem::transmute(())
+                }
+
+                /// Apply an init sequence to this peripheral
+                ///
+                /// The steps of the sequence are applied in order. This stops at the first wait
+                /// step that times out.
+                #[inline]
+                pub fn apply(
+                    &mut self,
+                    sequence: &$crate::InitSequence<[<Dyn $periph>]>,
+                ) -> ::core::result::Result<(), $crate::TimedOut> {
+                    unsafe { $crate::apply_sequence::<P, _>(sequence) }
                 }
             }
         }}}
 
-        $($crate::periph_inner!( $(#[$($reg_attr)*])* $rw $reg @ $offset : $int = $desc1 $desc2); )*
+        $($crate::periph_inner!(
+            $(#[$($reg_attr)*])* $rw $reg @ $offset [$endian $($reg_option)*] : $int [$($len)?]
+            $(as $access $($order)?)? = $desc1 $desc2
+        ); )*
+
+        $($crate::periph_attr_inner! { @impl { $([$($reg_attr)*])* } {} {
+            periph_inner: @bus_register $mode $reg [$($len)?] [$endian $($reg_option)*]
+                $(as $access)?
+        }})*
+
+        $crate::periph_attr_inner! { @type { $([$($periph_attr)*])* } {} {
+            periph_inner: @struct $periph dyn {$( $(#[$($reg_attr)*])* $reg [$($len)?] )*} {}
+        }}
+
+        $crate::periph_attr_inner! { @impl { $([$($periph_attr)*])* } {} {
+            periph_inner: @block $periph $mode $($size)? {$( $(#[$($reg_attr)*])* $reg [$($len)?] )*} {}
+        }}
 
         $crate::periph_attr_inner! { @type { $([$($periph_attr)*])* } {} {
-            periph_inner: @struct $periph dyn {$( $(#[$($reg_attr)*])* $reg )*} {}
+            periph_inner: @snapshot $periph
+                {$( $(#[$($reg_attr)*])* $rw $reg [$($len)?] {$($reg_option)*} )*} {}
+        }}
+
+        $crate::periph_attr_inner! { @impl { $([$($periph_attr)*])* } {} {
+            periph_inner: @snapshot_impl $periph
+                {$( $(#[$($reg_attr)*])* $rw $reg [$($len)?] {$($reg_option)*} )*} {}
         }}
     };
+    (
+        $(#[$($periph_attr:tt)*])*
+        $periph:ident $(: $size:literal)? bus;
+        $($rest:tt)*
+    ) => {
+        $crate::periph! {
+            $(#[$($periph_attr)*])*
+            $periph $(: $size)? native_endian bus;
+            $($rest)*
+        }
+    };
+    (
+        $(#[$($periph_attr:tt)*])*
+        $periph:ident $(: $size:literal)? $endian:ident;
+        $($rest:tt)*
+    ) => {
+        $crate::periph! {
+            $(#[$($periph_attr)*])*
+            $periph $(: $size)? $endian mmio;
+            $($rest)*
+        }
+    };
+    (
+        $(#[$($periph_attr:tt)*])*
+        $periph:ident $(: $size:literal)?;
+        $($rest:tt)*
+    ) => {
+        $crate::periph! {
+            $(#[$($periph_attr)*])*
+            $periph $(: $size)? native_endian mmio;
+            $($rest)*
+        }
+    };
 }
 
 #[macro_export]
 #[doc(hidden)]
 macro_rules! periph_inner {
-    ($(#[$($attr:tt)*])* $rw:ident $reg:ident @ $offset:literal : $int:ty = $desc:ty ; ) => {
+    (
+        $(#[$($attr:tt)*])* $rw:ident $reg:ident @ $offset:literal [$($option:ident)+] : $int:ty []
+        $(as $access:ident $($order:ident)?)? = $desc:ty ;
+    ) => {
         $crate::periph_attr_inner! { @type { $([$($attr)*])* } {} {
         periph_attr_inner: @expand
             #[derive(Debug)]
@@ -110,12 +444,23 @@ macro_rules! periph_inner {
 
                 const OFFSET: usize = $offset;
                 const NAME: &'static str = stringify!($reg);
+                const ENDIANNESS: $crate::Endianness = $crate::periph_inner!(@endian $($option)+);
+                $(
+                    const ACCESS_WIDTH: usize = $crate::periph_inner!(@access $access);
+                    $(const SPLIT_ORDER: $crate::SplitOrder = $crate::periph_inner!(@order $order);)?
+                )?
             }
         }}
 
         $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} { periph_inner: @impl $rw $reg }}
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+            periph_inner: @options $reg $($option)+
+        }}
     };
-    ($(#[$($attr:tt)*])* $rw:ident $reg:ident @ $offset:literal : $int:ty = $reset:literal $desc:tt) => {
+    (
+        $(#[$($attr:tt)*])* $rw:ident $reg:ident @ $offset:literal [$($option:ident)+] : $int:ty []
+        $(as $access:ident $($order:ident)?)? = $reset:literal $desc:tt
+    ) => {
         $crate::register!($(#[$($attr)*])* $reg: $int = $reset $desc);
 
         $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
@@ -126,10 +471,65 @@ macro_rules! periph_inner {
 
                 const OFFSET: usize = $offset;
                 const NAME: &'static str = stringify!($reg);
+                const ENDIANNESS: $crate::Endianness = $crate::periph_inner!(@endian $($option)+);
+                $(
+                    const ACCESS_WIDTH: usize = $crate::periph_inner!(@access $access);
+                    $(const SPLIT_ORDER: $crate::SplitOrder = $crate::periph_inner!(@order $order);)?
+                )?
             }
         }}
 
         $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} { periph_inner: @impl $rw $reg }}
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+            periph_inner: @options $reg $($option)+
+        }}
+    };
+
+    (
+        $(#[$($attr:tt)*])* $rw:ident $reg:ident @ $offset:literal [$($option:ident)+] : $int:ty
+        [$len:literal] = $desc:ty ;
+    ) => {
+        $crate::periph_attr_inner! { @type { $([$($attr)*])* } {} {
+        periph_attr_inner: @expand
+            #[derive(Debug)]
+            pub enum $reg {}
+        }}
+
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+        periph_attr_inner: @expand
+            impl $crate::Region for $reg {
+                type Int = $int;
+                type Value = $desc;
+
+                const OFFSET: usize = $offset;
+                const LEN: usize = $len;
+                const NAME: &'static str = stringify!($reg);
+                const ENDIANNESS: $crate::Endianness = $crate::periph_inner!(@endian $($option)+);
+            }
+        }}
+
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} { periph_inner: @region $rw $reg }}
+    };
+    (
+        $(#[$($attr:tt)*])* $rw:ident $reg:ident @ $offset:literal [$($option:ident)+] : $int:ty
+        [$len:literal] = $reset:literal $desc:tt
+    ) => {
+        $crate::register!($(#[$($attr)*])* $reg: $int = $reset $desc);
+
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+        periph_attr_inner: @expand
+            impl $crate::Region for $reg {
+                type Int = $int;
+                type Value = $reg;
+
+                const OFFSET: usize = $offset;
+                const LEN: usize = $len;
+                const NAME: &'static str = stringify!($reg);
+                const ENDIANNESS: $crate::Endianness = $crate::periph_inner!(@endian $($option)+);
+            }
+        }}
+
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} { periph_inner: @region $rw $reg }}
     };
 
     (@impl $(#[$attr:meta])* rw $reg:ident) => {
@@ -143,36 +543,372 @@ macro_rules! periph_inner {
         impl $crate::WriteRegister for $reg {}
     };
 
+    (@region $(#[$attr:meta])* rw $reg:ident) => {
+        impl $crate::ReadRegion for $reg {}
+        impl $crate::WriteRegion for $reg {}
+    };
+    (@region $(#[$attr:meta])* r $reg:ident) => {
+        impl $crate::ReadRegion for $reg {}
+    };
+    (@region $(#[$attr:meta])* w $reg:ident) => {
+        impl $crate::WriteRegion for $reg {}
+    };
+
+    (@options $(#[$attr:meta])* $reg:ident) => {};
+    (@options $(#[$attr:meta])* $reg:ident fifo $($rest:ident)*) => {
+        $(#[$attr])*
+        impl $crate::FifoRegister for $reg {}
+        $crate::periph_inner!(@options $(#[$attr])* $reg $($rest)*);
+    };
+    (@options $(#[$attr:meta])* $reg:ident $option:ident $($rest:ident)*) => {
+        $crate::periph_inner!(@options $(#[$attr])* $reg $($rest)*);
+    };
+
+    (@endian $current:ident fifo $($rest:ident)*) => {
+        $crate::periph_inner!(@endian $current $($rest)*)
+    };
+    (@endian $current:ident $next:ident $($rest:ident)*) => {
+        $crate::periph_inner!(@endian $next $($rest)*)
+    };
+    (@endian native_endian) => { $crate::Endianness::NATIVE };
+    (@endian little_endian) => { $crate::Endianness::Little };
+    (@endian big_endian) => { $crate::Endianness::Big };
+    (@access u8) => { 1 };
+    (@access u16) => { 2 };
+    (@access u32) => { 4 };
+    (@access u64) => { 8 };
+    (@access $access:ident) => {
+        compile_error!(concat!(
+            "invalid access width `", stringify!($access), "`, expected u8, u16, u32 or u64"
+        ))
+    };
+    (@order low_first) => { $crate::SplitOrder::LowFirst };
+    (@order high_first) => { $crate::SplitOrder::HighFirst };
+
     (@struct $periph:ident {} {$(#[$periph_attr:meta])*
-        $($reg:ident $(#[$attr:meta])*)*
+        $($reg:ident $handle:ident $dyn_handle:ident $(#[$attr:meta])*)*
     }) => { $crate::paste! {
         $(#[$periph_attr])*
         #[derive(Debug)]
         pub struct $periph<P: $crate::Peripheral> {$(
             $(#[$attr])*
-            pub [<$reg:lower>]: $crate::Reg<$reg, P>,
+            pub [<$reg:lower>]: $crate::$handle<$reg, P>,
         )*}
     }};
     (@struct $periph:ident dyn {} {$(#[$periph_attr:meta])*
-        $($reg:ident $(#[$attr:meta])*)*
+        $($reg:ident $handle:ident $dyn_handle:ident $(#[$attr:meta])*)*
     }) => { $crate::paste! {
         $(#[$periph_attr])*
         #[derive(Debug)]
         pub struct [<Dyn $periph>] {$(
             $(#[$attr])*
-            pub [<$reg:lower>]: $crate::DynReg<$reg>,
+            pub [<$reg:lower>]: $crate::$dyn_handle<$reg>,
+        )*}
+    }};
+    (@block $periph:ident $mode:ident $($size:literal)? {} {$(#[$periph_attr:meta])*
+        $($reg:ident $kind:tt $(#[$attr:meta])*)*
+    }) => { $crate::paste! {
+        $(#[$periph_attr])*
+        impl $crate::RegisterBlock for [<Dyn $periph>] {
+            #[allow(unused_doc_comments)]
+            const SIZE: usize = $crate::block_size(
+                $crate::periph_inner!(@size $($size)?),
+                &[$(
+                    $(#[$attr])*
+                    $crate::periph_inner!(@layout $reg $kind),
+                )*],
+                $crate::periph_inner!(@aligned $mode),
+            );
+            #[allow(unused_doc_comments)]
+            const ALIGN: usize = $crate::block_align(&[$(
+                $(#[$attr])*
+                $crate::periph_inner!(@layout $reg $kind),
+            )*]);
+        }
+
+        $crate::periph_inner!(@mmio $mode {
+        $(#[$periph_attr])*
+        unsafe impl $crate::DynBlock for [<Dyn $periph>] {}
+
+        $(#[$periph_attr])*
+        impl [<Dyn $periph>] {
+            /// Create the peripheral at a base address known at runtime
+            ///
+            /// This is useful when the address comes e.g. from a device tree, a PCI BAR or a
+            /// memory mapping. The returned reference can have any lifetime, which should be
+            /// limited to the lifetime of the mapping.
+            ///
+            /// # Safety
+            ///
+            /// The register block must be mapped at `base` for the whole lifetime `'a`, and must
+            /// not be accessed through other references during this lifetime.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `base` is not aligned to the alignment of the register block.
+            #[inline]
+            pub unsafe fn from_base<'a>(base: usize) -> &'a mut [<Dyn $periph>] {
+                $crate::block_from_base(base)
+            }
+        }
+
+        $(#[$periph_attr])*
+        impl<P: $crate::Peripheral> $periph<P> {
+            /// Erase peripheral information
+            ///
+            /// This allows to choose at runtime which instance of a peripheral to use.
+            #[inline]
+            pub fn into_dyn(self) -> &'static mut [<Dyn $periph>] {
+                unsafe { [<Dyn $periph>]::from_base(P::BASE) }
+            }
+        }
+
+        $(#[$periph_attr])*
+        impl<P: $crate::Peripheral> $crate::RegisterBlock for $periph<P> {
+            const SIZE: usize = <[<Dyn $periph>] as $crate::RegisterBlock>::SIZE;
+            const ALIGN: usize = <[<Dyn $periph>] as $crate::RegisterBlock>::ALIGN;
+        }
+        });
+
+        // Check the layout even if the peripheral is not used in a device
+        $(#[$periph_attr])*
+        const _: usize = <[<Dyn $periph>] as $crate::RegisterBlock>::SIZE;
+
+        $(#[$periph_attr])*
+        #[allow(unused_doc_comments)]
+        const _: () = {$(
+            $(#[$attr])*
+            $crate::periph_inner!(@register_of [<Dyn $periph>] $reg $kind);
+        )*};
+    }};
+    (@block $(#[$attr:meta])* $periph:ident $mode:ident $($size:literal)? {} {$($rest:tt)*} ) => {
+        $crate::periph_inner!(@block $periph $mode $($size)? {} { $($rest)* $(#[$attr])* } );
+    };
+    (@block
+        $(#[$prev:meta])* $periph:ident $mode:ident $($size:literal)?
+        { $(#[$($attr:tt)*])* $reg:ident $kind:tt $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+            periph_inner: @block $periph $mode $($size)? { $($rest)* }
+                { $($parsed)* $(#[$prev])* $reg $kind }
+        }}
+    };
+    (@bus_register $(#[$attr:meta])* mmio $($rest:tt)*) => {};
+    (@bus_register $(#[$attr:meta])* bus $reg:ident [$len:literal] $($rest:tt)*) => {};
+    (@bus_register $(#[$attr:meta])* bus $reg:ident [] [$($option:ident)*] as $access:ident) => {
+        ::core::compile_error!("registers accessed through a bus can't have another access width");
+    };
+    (@bus_register $(#[$attr:meta])* bus $reg:ident [] [native_endian $($option:ident)*]) => {
+        $crate::periph_inner!(@bus_register $(#[$attr])* bus $reg [] [$($option)*]);
+    };
+    (@bus_register $(#[$attr:meta])* bus $reg:ident [] [fifo $($option:ident)*]) => {
+        $crate::periph_inner!(@bus_register $(#[$attr])* bus $reg [] [$($option)*]);
+    };
+    (@bus_register $(#[$attr:meta])* bus $reg:ident [] [$option:ident $($rest:ident)*]) => {
+        ::core::compile_error!("registers accessed through a bus have the byte order of the bus");
+    };
+    (@bus_register $(#[$attr:meta])* bus $reg:ident [] []) => {
+        impl $crate::BusRegister for $reg {}
+    };
+    (@aligned mmio) => { true };
+    (@aligned bus) => { false };
+    (@mmio mmio { $($item:item)* }) => { $($item)* };
+    (@mmio bus { $($item:item)* }) => {};
+
+    (@snapshot $periph:ident {} {$(#[$periph_attr:meta])*
+        $($reg:ident $(#[$attr:meta])*)*
+    }) => { $crate::paste! {
+        #[doc = "Snapshot of the readable registers of a [`" $periph "`]"]
+        ///
+        /// This is created by the `snapshot` method of the peripheral, and written back by its
+        /// `restore` method.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct [<$periph Snapshot>] {$(
+            $(#[$attr])*
+            pub [<$reg:lower>]: $crate::Value<<$reg as $crate::Register>::Value>,
         )*}
     }};
+    (@snapshot $(#[$attr:meta])* $periph:ident {} {$($rest:tt)*} ) => {
+        $crate::periph_inner!(@snapshot $periph {} { $($rest)* $(#[$attr])* } );
+    };
+    (@snapshot
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [$len:literal] {$($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot $periph { $($rest)* } { $($parsed)* $(#[$prev])* });
+    };
+    (@snapshot
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* w $reg:ident [] {$($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot $periph { $($rest)* } { $($parsed)* $(#[$prev])* });
+    };
+    (@snapshot
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [] {fifo $($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot $periph { $($rest)* } { $($parsed)* $(#[$prev])* });
+    };
+    (@snapshot
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [] {$other:ident $($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot $(#[$prev])* $periph
+            { $(#[$($attr)*])* $rw $reg [] {$($option)*} $($rest)* }
+            { $($parsed)* }
+        );
+    };
+    (@snapshot
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [] {} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_attr_inner! { @field { $([$($attr)*])* } {} {
+            periph_inner: @snapshot $periph { $($rest)* } { $($parsed)* $(#[$prev])* $reg }
+        }}
+    };
+
+    (@snapshot_impl $periph:ident {} {$(#[$periph_attr:meta])*
+        $($rw:ident $reg:ident $(#[$attr:meta])*)*
+    }) => { $crate::paste! {
+        $(#[$periph_attr])*
+        impl<P: $crate::Peripheral> $periph<P> {
+            /// Read all readable registers
+            #[inline]
+            #[allow(unused_doc_comments)]
+            pub fn snapshot(&self) -> [<$periph Snapshot>] {
+                [<$periph Snapshot>] {$(
+                    $(#[$attr])*
+                    [<$reg:lower>]: self.[<$reg:lower>].read(),
+                )*}
+            }
+
+            /// Write back a snapshot
+            ///
+            /// Registers that are both readable and writeable are written in declaration order.
+            /// To use another order, restore each register with its `restore_` method.
+            #[inline]
+            #[allow(unused_doc_comments, unused_variables)]
+            pub fn restore(&mut self, snapshot: &[<$periph Snapshot>]) {$(
+                $(#[$attr])*
+                $crate::periph_inner!(@restore $rw self.[<$reg:lower>], snapshot.[<$reg:lower>]);
+            )*}
+
+            $($crate::periph_inner!(
+                @restore_fn $rw $reg [<restore_ $reg:lower>] [<$reg:lower>] [<$periph Snapshot>]
+                $(#[$attr])*
+            );)*
+        }
+
+        $(#[$periph_attr])*
+        impl [<$periph Snapshot>] {
+            /// List the fields that differ from an other snapshot
+            ///
+            /// Fields are listed in declaration order, with their value in `self` as old value.
+            #[inline]
+            pub fn diff(
+                &self,
+                other: &[<$periph Snapshot>],
+            ) -> impl ::core::iter::Iterator<Item = $crate::FieldChange> {
+                #[allow(unused_doc_comments)]
+                $crate::field_changes([$(
+                    $(#[$attr])*
+                    $crate::FieldChanges::new::<$reg>(self.[<$reg:lower>], other.[<$reg:lower>]),
+                )*])
+            }
+        }
+    }};
+    (@snapshot_impl $(#[$attr:meta])* $periph:ident {} {$($rest:tt)*} ) => {
+        $crate::periph_inner!(@snapshot_impl $periph {} { $($rest)* $(#[$attr])* } );
+    };
+    (@snapshot_impl
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [$len:literal] {$($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot_impl $periph { $($rest)* } { $($parsed)* $(#[$prev])* });
+    };
+    (@snapshot_impl
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* w $reg:ident [] {$($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot_impl $periph { $($rest)* } { $($parsed)* $(#[$prev])* });
+    };
+    (@snapshot_impl
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [] {fifo $($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot_impl $periph { $($rest)* } { $($parsed)* $(#[$prev])* });
+    };
+    (@snapshot_impl
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [] {$other:ident $($option:ident)*} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_inner!(@snapshot_impl $(#[$prev])* $periph
+            { $(#[$($attr)*])* $rw $reg [] {$($option)*} $($rest)* }
+            { $($parsed)* }
+        );
+    };
+    (@snapshot_impl
+        $(#[$prev:meta])* $periph:ident
+        { $(#[$($attr:tt)*])* $rw:ident $reg:ident [] {} $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_attr_inner! { @impl { $([$($attr)*])* } {} {
+            periph_inner: @snapshot_impl $periph { $($rest)* } { $($parsed)* $(#[$prev])* $rw $reg }
+        }}
+    };
+    (@restore rw $reg:expr, $value:expr) => { $reg.write($value) };
+    (@restore r $reg:expr, $value:expr) => {};
+    (@restore_fn rw $reg:ident $name:ident $field:ident $snapshot:ident $(#[$attr:meta])*) => {
+        #[doc = concat!("Write back the value of `", stringify!($reg), "` from a snapshot")]
+        $(#[$attr])*
+        #[inline]
+        pub fn $name(&mut self, snapshot: &$snapshot) {
+            self.$field.write(snapshot.$field);
+        }
+    };
+    (@restore_fn r $($rest:tt)*) => {};
+
+    (@register_of $block:ident $reg:ident []) => {
+        impl $crate::RegisterOf<$block> for $reg {}
+    };
+    (@register_of $block:ident $reg:ident [$len:literal]) => {};
+
+    (@size) => { ::core::option::Option::None };
+    (@size $size:literal) => { ::core::option::Option::Some($size) };
+    (@layout $reg:ident []) => { $crate::register_layout::<$reg>() };
+    (@layout $reg:ident [$len:literal]) => { $crate::region_layout::<$reg>() };
+
     (@struct $(#[$attr:meta])* $periph:ident $($type:ident)? {} {$($rest:tt)*} ) => {
         $crate::periph_inner!(@struct $periph $($type)? {} { $($rest)* $(#[$attr])* } );
     };
     (@struct
         $(#[$prev:meta])* $periph:ident $($type:ident)?
-        { $(#[$($attr:tt)*])* $reg:ident $($rest:tt)* }
+        { $(#[$($attr:tt)*])* $reg:ident [] $($rest:tt)* }
+        { $($parsed:tt)* }
+    ) => {
+        $crate::periph_attr_inner! { @field { $([$($attr)*])* } {} {
+            periph_inner: @struct $periph $($type)? { $($rest)* }
+                { $($parsed)* $(#[$prev])* $reg Reg DynReg }
+        }}
+    };
+    (@struct
+        $(#[$prev:meta])* $periph:ident $($type:ident)?
+        { $(#[$($attr:tt)*])* $reg:ident [$len:literal] $($rest:tt)* }
         { $($parsed:tt)* }
     ) => {
         $crate::periph_attr_inner! { @field { $([$($attr)*])* } {} {
-            periph_inner: @struct $periph $($type)? { $($rest)* } { $($parsed)* $(#[$prev])* $reg }
+            periph_inner: @struct $periph $($type)? { $($rest)* }
+                { $($parsed)* $(#[$prev])* $reg Memory DynMemory }
         }}
     };
 }
@@ -36,7 +36,6 @@
 ///     struct Type [u16] (u8);
 /// }
 /// ```
-
 #[macro_export]
 macro_rules! register {
     ($(#[$($attr:tt)*])* $reg:ident: $type:ty = $reset:literal {$($fields:tt)*}) => {
@@ -59,6 +58,14 @@ macro_rules! register {
                 type Int = $type;
                 const RESET: $type = $reset;
                 const NAME: &'static str = stringify!($reg);
+                const FIELDS: &'static [$crate::FieldInfo] =
+                    &$crate::register_inner!(@info $reg [] $($fields)*);
+
+                #[inline]
+                #[allow(unused_variables)]
+                fn is_valid(value: $type) -> bool {
+                    $crate::register_inner!(@valid $reg value [] $($fields)*)
+                }
             }
         }}
 
@@ -202,6 +209,45 @@ macro_rules! register_inner {
         }
     };
 
+    (@info $reg:ident [$($info:expr,)*]) => { [$($info,)*] };
+    (@info $reg:ident [$($info:expr,)*] $(#[$($attr:tt)*])*
+        $field:ident: $start:literal $(.. $end:literal)? = enum $name:ident $desc:tt $($rest:tt)*
+    ) => {
+        $crate::register_inner!(@info $reg [$($info,)* $crate::register_inner!(@info $reg $field),] $($rest)*)
+    };
+    (@info $reg:ident [$($info:expr,)*] $(#[$($attr:tt)*])*
+        $field:ident: $start:literal $(.. $end:literal)? = struct $name:ident $desc:tt; $($rest:tt)*
+    ) => {
+        $crate::register_inner!(@info $reg [$($info,)* $crate::register_inner!(@info $reg $field),] $($rest)*)
+    };
+    (@info $reg:ident [$($info:expr,)*] $(#[$($attr:tt)*])*
+        $field:ident: $start:literal $(.. $end:literal)? = extern $name:ty; $($rest:tt)*
+    ) => {
+        $crate::register_inner!(@info $reg [$($info,)* $crate::register_inner!(@info $reg $field),] $($rest)*)
+    };
+    (@info $reg:ident $field:ident) => {
+        $crate::FieldInfo::new(stringify!($field), $reg::$field.mask() as u128, $reg::$field.offset())
+    };
+
+    (@valid $reg:ident $value:ident [$($field:ident)*]) => {
+        $($crate::field_is_valid($reg::$field, $value) &&)* true
+    };
+    (@valid $reg:ident $value:ident [$($parsed:ident)*] $(#[$($attr:tt)*])*
+        $field:ident: $start:literal $(.. $end:literal)? = enum $name:ident $desc:tt $($rest:tt)*
+    ) => {
+        $crate::register_inner!(@valid $reg $value [$($parsed)* $field] $($rest)*)
+    };
+    (@valid $reg:ident $value:ident [$($parsed:ident)*] $(#[$($attr:tt)*])*
+        $field:ident: $start:literal $(.. $end:literal)? = struct $name:ident $desc:tt; $($rest:tt)*
+    ) => {
+        $crate::register_inner!(@valid $reg $value [$($parsed)* $field] $($rest)*)
+    };
+    (@valid $reg:ident $value:ident [$($parsed:ident)*] $(#[$($attr:tt)*])*
+        $field:ident: $start:literal $(.. $end:literal)? = extern $name:ty; $($rest:tt)*
+    ) => {
+        $crate::register_inner!(@valid $reg $value [$($parsed)* $field] $($rest)*)
+    };
+
     (@reg $reg:ident $type:ty: ) => {};
     (@reg $reg:ident $type:ty: $(#[$($attr:tt)*])*
         $field:ident: $start:literal $(.. $end:literal)? = enum $name:ident $desc:tt $($rest:tt)*
@@ -0,0 +1,226 @@
+use super::*;
+use core::mem::size_of;
+
+/// The order of the accesses to a register split in several accesses
+///
+/// This is used when the accesses to a register are narrower than the register, see
+/// [`Register::ACCESS_WIDTH`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SplitOrder {
+    /// The least significant part is accessed first
+    LowFirst,
+    /// The most significant part is accessed first
+    HighFirst,
+}
+
+/// The accesses of a peripheral instance
+pub(crate) struct Instance<P>(PhantomData<P>);
+
+impl<P: Peripheral> Instance<P> {
+    pub(crate) const fn new() -> Instance<P> {
+        Instance(PhantomData)
+    }
+}
+
+impl<P: Peripheral> Backend for Instance<P> {
+    #[inline]
+    fn read(&self, offset: usize, width: usize) -> u128 {
+        let address = P::BASE + offset;
+        unsafe {
+            match width {
+                1 => P::read::<u8>(address).to_u128(),
+                2 => P::read::<u16>(address).to_u128(),
+                4 => P::read::<u32>(address).to_u128(),
+                8 => P::read::<u64>(address).to_u128(),
+                _ => P::read::<u128>(address),
+            }
+        }
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, width: usize, value: u128) {
+        let address = P::BASE + offset;
+        unsafe {
+            match width {
+                1 => P::write(address, value as u8),
+                2 => P::write(address, value as u16),
+                4 => P::write(address, value as u32),
+                8 => P::write(address, value as u64),
+                _ => P::write(address, value),
+            }
+        }
+    }
+}
+
+/// Volatile accesses from a base address
+pub(crate) struct Pointer(usize);
+
+impl Pointer {
+    /// # Safety
+    ///
+    /// The registers accessed must be valid for volatile reads and writes.
+    pub(crate) const unsafe fn new(base: usize) -> Pointer {
+        Pointer(base)
+    }
+}
+
+impl Backend for Pointer {
+    #[inline]
+    fn read(&self, offset: usize, width: usize) -> u128 {
+        let address = self.0 + offset;
+        unsafe {
+            match width {
+                1 => (address as *const u8).read_volatile().to_u128(),
+                2 => (address as *const u16).read_volatile().to_u128(),
+                4 => (address as *const u32).read_volatile().to_u128(),
+                8 => (address as *const u64).read_volatile().to_u128(),
+                _ => (address as *const u128).read_volatile(),
+            }
+        }
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, width: usize, value: u128) {
+        let address = self.0 + offset;
+        unsafe {
+            match width {
+                1 => (address as *mut u8).write_volatile(value as u8),
+                2 => (address as *mut u16).write_volatile(value as u16),
+                4 => (address as *mut u32).write_volatile(value as u32),
+                8 => (address as *mut u64).write_volatile(value as u64),
+                _ => (address as *mut u128).write_volatile(value),
+            }
+        }
+    }
+}
+
+/// The offsets and shifts of the parts of a register, in access order
+fn parts<R: Register>() -> impl Iterator<Item = (usize, usize)> {
+    let access = R::ACCESS_WIDTH;
+    let count = size_of::<R::Int>() / access;
+    (0..count).map(move |part| {
+        let part = match R::SPLIT_ORDER {
+            SplitOrder::LowFirst => part,
+            SplitOrder::HighFirst => count - 1 - part,
+        };
+        // The address of the least significant part depends on the native byte order
+        let offset = match Endianness::NATIVE {
+            Endianness::Little => part * access,
+            Endianness::Big => (count - 1 - part) * access,
+        };
+        (offset, part * access * 8)
+    })
+}
+
+/// Read a register, with the access width and byte order of the register
+#[inline]
+pub(crate) fn read_register<R: Register, B: Backend + ?Sized>(
+    backend: &B,
+    offset: usize,
+) -> R::Int {
+    let value = if R::ACCESS_WIDTH >= size_of::<R::Int>() {
+        backend.read(offset, R::ACCESS_WIDTH)
+    } else {
+        parts::<R>().fold(0, |value, (part, shift)| {
+            value | backend.read(offset + part, R::ACCESS_WIDTH) << shift
+        })
+    };
+    swap_bytes::<R>(R::Int::from_u128(value))
+}
+
+/// Write a register, with the access width and byte order of the register
+#[inline]
+pub(crate) fn write_register<R: Register, B: Backend + ?Sized>(
+    backend: &B,
+    offset: usize,
+    value: R::Int,
+) {
+    let value = swap_bytes::<R>(value).to_u128();
+    if R::ACCESS_WIDTH >= size_of::<R::Int>() {
+        backend.write(offset, R::ACCESS_WIDTH, value);
+    } else {
+        let mask = !(!0 << (R::ACCESS_WIDTH * 8));
+        for (part, shift) in parts::<R>() {
+            backend.write(offset + part, R::ACCESS_WIDTH, (value >> shift) & mask);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod access_width {
+        crate::periph! {
+            AccessWidth;
+            rw CONTROL @ 0x00: u8 as u32 = 0x00 {
+                ENABLE: 0 = struct Enable(bool);
+            }
+            r COUNTER @ 0x08: u64 as u32 high_first = 0x0000 {}
+            w COMPARE @ 0x10: u64 as u16 = 0x0000 {}
+        }
+    }
+
+    #[test]
+    fn access_width() {
+        use access_width::{Enable, COMPARE, CONTROL, COUNTER};
+
+        assert_eq!(<access_width::DynAccessWidth as RegisterBlock>::SIZE, 0x18);
+        assert_eq!(<access_width::DynAccessWidth as RegisterBlock>::ALIGN, 4);
+
+        let mut control = unsafe { Reg::<CONTROL, Mock<PERIPH>>::steal() };
+        let counter = unsafe { Reg::<COUNTER, Mock<PERIPH>>::steal() };
+        let mut compare = unsafe { Reg::<COMPARE, Mock<PERIPH>>::steal() };
+        mock::set::<COUNTER, PERIPH>(0x0123_4567_89ab_cdef);
+        control.modify(Enable(true));
+        assert_eq!(counter.read().value(), 0x0123_4567_89ab_cdef);
+        compare.write(unsafe { Value::from_raw(0x0123_4567_89ab_cdef) });
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 4,
+                    value: 0x0000_0000,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 4,
+                    value: 0x0000_0001,
+                },
+                Access::Read {
+                    address: 0x4001_000c,
+                    width: 4,
+                    value: 0x0123_4567,
+                },
+                Access::Read {
+                    address: 0x4001_0008,
+                    width: 4,
+                    value: 0x89ab_cdef,
+                },
+                Access::Write {
+                    address: 0x4001_0010,
+                    width: 2,
+                    value: 0xcdef,
+                },
+                Access::Write {
+                    address: 0x4001_0012,
+                    width: 2,
+                    value: 0x89ab,
+                },
+                Access::Write {
+                    address: 0x4001_0014,
+                    width: 2,
+                    value: 0x4567,
+                },
+                Access::Write {
+                    address: 0x4001_0016,
+                    width: 2,
+                    value: 0x0123,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use super::*;
+use core::sync::atomic::{compiler_fence, fence, Ordering};
+
+/// A memory barrier around register accesses
+///
+/// Register accesses are volatile, which prevents the compiler from reordering them with other
+/// volatile accesses, but not with accesses to normal memory. For example, the writes to a DMA
+/// buffer may happen after the write to the register starting the transfer. The `_fenced` methods
+/// of [`Reg`] and [`DynReg`] call [`Barrier::before`] before accessing the register, and
+/// [`Barrier::after`] after it.
+///
+/// This trait is implemented by [`CompilerFence`] and [`Fence`]. It can be implemented for other
+/// types, e.g. to use a data synchronization barrier instruction:
+///
+/// ```no_run
+/// # peripherals::periph!{
+/// #     Dma;
+/// #     rw ADDRESS @ 0x00: u32 = 0x0000 {}
+/// #     rw START @ 0x04: u32 = 0x0000 {
+/// #         START: 0 = struct Start(bool);
+/// #     }
+/// # }
+/// # peripherals::device!{
+/// #     MyMicrocontroller;
+/// #     DMA @ 0x4002_0000: Dma;
+/// # }
+/// use peripherals::{Barrier, Value};
+///
+/// struct Dsb;
+///
+/// impl Barrier for Dsb {
+///     fn before() {
+///         // e.g. `cortex_m::asm::dsb()`
+///     }
+/// }
+///
+/// let mut dma = MyMicrocontroller::take().unwrap().dma;
+/// let mut buffer = [0u8; 64];
+/// buffer[0] = 0x42;
+///
+/// dma.address.write(unsafe { Value::from_raw(buffer.as_ptr() as u32) });
+/// // The write to `buffer` is completed before the transfer starts
+/// dma.start.write_fenced::<Dsb>(Value::reset() | Start(true));
+/// ```
+pub trait Barrier {
+    /// Called before the register access
+    #[inline]
+    fn before() {}
+
+    /// Called after the register access
+    #[inline]
+    fn after() {}
+}
+
+/// A compiler fence around register accesses
+///
+/// This prevents the compiler from reordering memory accesses around register accesses, with
+/// [`compiler_fence`]. This is enough on single-core microcontrollers without caches or write
+/// buffers.
+#[derive(Debug)]
+pub enum CompilerFence {}
+
+impl Barrier for CompilerFence {
+    #[inline]
+    fn before() {
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn after() {
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+/// A memory fence around register accesses
+///
+/// This prevents both the compiler and the CPU from reordering memory accesses around register
+/// accesses, with [`fence`].
+#[derive(Debug)]
+pub enum Fence {}
+
+impl Barrier for Fence {
+    #[inline]
+    fn before() {
+        fence(Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn after() {
+        fence(Ordering::SeqCst);
+    }
+}
+
+impl<R: ReadRegister, P: Peripheral> Reg<R, P> {
+    /// Read this register between barriers
+    ///
+    /// Same as [`Reg::read`], with the barrier `B` before and after the read. This allows e.g. to
+    /// read a DMA buffer after reading that the transfer is done.
+    #[inline]
+    pub fn read_fenced<B: Barrier>(&self) -> Value<R::Value> {
+        B::before();
+        let value = self.read();
+        B::after();
+        value
+    }
+}
+
+impl<R: WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Write this register between barriers
+    ///
+    /// Same as [`Reg::write`], with the barrier `B` before and after the write. See [`Barrier`] for
+    /// an example.
+    #[inline]
+    pub fn write_fenced<B: Barrier>(&mut self, value: Value<R::Value>) {
+        B::before();
+        self.write(value);
+        B::after();
+    }
+}
+
+impl<R: ReadRegister + WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Modify this register between barriers
+    ///
+    /// Same as [`Reg::modify`], with the barrier `B` before the read and after the write.
+    #[inline]
+    pub fn modify_fenced<B: Barrier, V: Into<FieldValues<R::Value>>>(&mut self, bits: V) {
+        B::before();
+        self.modify(bits);
+        B::after();
+    }
+}
+
+impl<R: ReadRegister> DynReg<R> {
+    /// Read this register between barriers
+    ///
+    /// Same as [`DynReg::read`], with the barrier `B` before and after the read.
+    #[inline]
+    pub fn read_fenced<B: Barrier>(&self) -> Value<R::Value> {
+        B::before();
+        let value = self.read();
+        B::after();
+        value
+    }
+}
+
+impl<R: WriteRegister> DynReg<R> {
+    /// Write this register between barriers
+    ///
+    /// Same as [`DynReg::write`], with the barrier `B` before and after the write.
+    #[inline]
+    pub fn write_fenced<B: Barrier>(&mut self, value: Value<R::Value>) {
+        B::before();
+        self.write(value);
+        B::after();
+    }
+}
+
+impl<R: ReadRegister + WriteRegister> DynReg<R> {
+    /// Modify this register between barriers
+    ///
+    /// Same as [`DynReg::modify`], with the barrier `B` before the read and after the write.
+    #[inline]
+    pub fn modify_fenced<B: Barrier, V: Into<FieldValues<R::Value>>>(&mut self, bits: V) {
+        B::before();
+        self.modify(bits);
+        B::after();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{Mode, CONFIG};
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+
+    #[allow(clippy::upper_case_acronyms)]
+    enum IRQ {}
+
+    impl Peripheral for IRQ {
+        const BASE: usize = 0x4002_0000;
+        const NAME: &'static str = "IRQ";
+    }
+
+    /// A barrier writing markers to the IRQ peripheral
+    enum Marker {}
+
+    impl Barrier for Marker {
+        fn before() {
+            unsafe { Mock::<IRQ>::write(IRQ::BASE, 1u8) }
+        }
+
+        fn after() {
+            unsafe { Mock::<IRQ>::write(IRQ::BASE, 2u8) }
+        }
+    }
+
+    #[test]
+    fn fenced() {
+        let mut config = unsafe { Reg::<CONFIG, Mock<PERIPH>>::steal() };
+        config.write_fenced::<Marker>(Value::reset() | Mode::B);
+        config.modify_fenced::<CompilerFence, _>(Mode::C);
+        assert_eq!(config.read_fenced::<Fence>().field(CONFIG::MODE), Mode::C);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4002_0000,
+                    width: 1,
+                    value: 1,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+                Access::Write {
+                    address: 0x4002_0000,
+                    width: 1,
+                    value: 2,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0002,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0002,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use super::*;
+
+/// A trait for the register block of a peripheral
+///
+/// This trait is implemented by the [`periph!`] macro for the generated peripheral struct and its
+/// `Dyn` counterpart. It is used by the [`device!`] macro to check that peripheral instances don't
+/// overlap.
+pub trait RegisterBlock {
+    /// The size of the register block, in bytes
+    ///
+    /// This is either the size given to the [`periph!`] macro, or the end of the last register.
+    const SIZE: usize;
+    /// The alignment required for the base address
+    ///
+    /// This is the largest alignment of the registers of the block.
+    const ALIGN: usize;
+}
+
+/// A marker trait for register blocks whose registers are at offsets from their own address
+///
+/// This trait is implemented by the [`periph!`] macro for the `Dyn` peripheral struct only. The
+/// peripheral struct is zero-sized and accesses the absolute address of its instance, so it must
+/// not be placed at another address.
+///
+/// # Safety
+///
+/// All accesses through the type must be relative to the address of the reference.
+#[doc(hidden)]
+pub unsafe trait DynBlock: RegisterBlock {}
+
+/// Compute the offset, size and alignment of a register
+///
+/// Accesses wider than the register extend its size, and accesses of another width than the
+/// register give the alignment of the register.
+#[doc(hidden)]
+#[inline]
+pub const fn register_layout<R: Register>() -> (usize, usize, usize) {
+    let width = core::mem::size_of::<R::Int>();
+    if R::ACCESS_WIDTH == width {
+        (R::OFFSET, width, core::mem::align_of::<R::Int>())
+    } else if R::ACCESS_WIDTH > width {
+        (R::OFFSET, R::ACCESS_WIDTH, R::ACCESS_WIDTH)
+    } else {
+        (R::OFFSET, width, R::ACCESS_WIDTH)
+    }
+}
+
+/// Compute the size of a register block
+///
+/// `registers` contains the offset, size and alignment of each register. Registers need to be
+/// aligned if `aligned` is true, i.e. if they are accessed in memory.
+#[doc(hidden)]
+#[inline]
+pub const fn block_size(
+    declared: Option<usize>,
+    registers: &[(usize, usize, usize)],
+    aligned: bool,
+) -> usize {
+    let mut size = 0;
+    let mut i = 0;
+    while i < registers.len() {
+        let (offset, width, align) = registers[i];
+        if aligned && offset % align != 0 {
+            panic!("misaligned register");
+        }
+        if offset + width > size {
+            size = offset + width;
+        }
+        i += 1;
+    }
+
+    match declared {
+        Some(declared) if declared < size => panic!("registers don't fit in the declared size"),
+        Some(declared) => declared,
+        None => size,
+    }
+}
+
+/// Compute the alignment of a register block
+///
+/// `registers` contains the offset, size and alignment of each register.
+#[doc(hidden)]
+#[inline]
+pub const fn block_align(registers: &[(usize, usize, usize)]) -> usize {
+    let mut align = 1;
+    let mut i = 0;
+    while i < registers.len() {
+        if registers[i].2 > align {
+            align = registers[i].2;
+        }
+        i += 1;
+    }
+    align
+}
+
+/// Create a register block at the given base address
+///
+/// # Safety
+///
+/// See the `from_base` function generated by the [`periph!`] macro.
+#[doc(hidden)]
+#[inline]
+pub unsafe fn block_from_base<'a, B: DynBlock>(base: usize) -> &'a mut B {
+    // The alignment of a register block is a power of two
+    if base & (B::ALIGN - 1) != 0 {
+        panic!("misaligned register block");
+    }
+    &mut *(base as *mut B)
+}
+
+/// Check that peripheral instances are aligned and don't overlap
+///
+/// `instances` contains the base address, size and alignment of each peripheral instance.
+#[doc(hidden)]
+#[inline]
+pub const fn check_instances(instances: &[(usize, usize, usize)]) {
+    let mut i = 0;
+    while i < instances.len() {
+        let (base, size, align) = instances[i];
+        if base % align != 0 {
+            panic!("misaligned peripheral instance");
+        }
+
+        let mut j = i + 1;
+        while j < instances.len() {
+            let (other, other_size, _) = instances[j];
+            if base < other + other_size && other < base + size {
+                panic!("overlapping peripheral instances");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Check that a peripheral instance is in a bit-band region
+#[doc(hidden)]
+#[inline]
+pub const fn check_bit_band(base: usize, size: usize) {
+    let base = base as u32;
+    let region = base & 0xf000_0000;
+    if region != 0x2000_0000 && region != 0x4000_0000 || base - region + size as u32 > 0x0010_0000 {
+        panic!("peripheral instance outside of bit-band regions");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{self, DynPeripheral, Mode};
+    use crate::utils::mock::PERIPH;
+
+    #[test]
+    fn block() {
+        assert_eq!(<periph::Peripheral<PERIPH> as RegisterBlock>::SIZE, 6);
+        assert_eq!(<periph::Peripheral<PERIPH> as RegisterBlock>::ALIGN, 2);
+        assert_eq!(<DynPeripheral as RegisterBlock>::SIZE, 6);
+        assert_eq!(<DynPeripheral as RegisterBlock>::ALIGN, 2);
+    }
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod unaligned {
+        crate::periph! {
+            Unaligned bus;
+            rw CONFIG @ 0x00: u16 = 0x0000 {}
+            r SHUNT @ 0x01: u16 = 0x0000 {}
+        }
+    }
+
+    #[test]
+    fn unaligned_block() {
+        assert_eq!(<unaligned::DynUnaligned as RegisterBlock>::SIZE, 3);
+        assert_eq!(<unaligned::DynUnaligned as RegisterBlock>::ALIGN, 2);
+    }
+
+    #[test]
+    fn from_base() {
+        let mut memory = [0u16; 3];
+        let base = memory.as_mut_ptr() as usize;
+        let periph = unsafe { DynPeripheral::from_base(base) };
+        assert_eq!(periph.status.ptr() as usize, base + 2);
+        periph.config.modify(Mode::C);
+        assert_eq!(memory[0], 0x0002);
+    }
+
+    #[test]
+    #[should_panic = "misaligned register block"]
+    fn from_base_misaligned() {
+        let mut memory = [0u16; 4];
+        let base = memory.as_mut_ptr() as usize + 1;
+        unsafe { DynPeripheral::from_base(base) };
+    }
+}
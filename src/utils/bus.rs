@@ -0,0 +1,812 @@
+use super::*;
+use core::future::Future;
+
+/// A serial bus giving access to the registers of an external device, e.g. over I2C or SPI
+///
+/// Implement this trait for your bus driver to access the registers of a device with a [`Bus`].
+/// The register address is sent first, followed by the data read or written. Both are already
+/// encoded with the address width and endianness of the [`Bus`].
+pub trait Interface {
+    /// The error returned by bus transfers
+    type Error;
+
+    /// Read `data.len()` bytes from the register at `address`
+    fn read(&mut self, address: &[u8], data: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write `data` to the register at `address`
+    fn write(&mut self, address: &[u8], data: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<I: Interface + ?Sized> Interface for &mut I {
+    type Error = I::Error;
+
+    #[inline]
+    fn read(&mut self, address: &[u8], data: &mut [u8]) -> Result<(), I::Error> {
+        (**self).read(address, data)
+    }
+
+    #[inline]
+    fn write(&mut self, address: &[u8], data: &[u8]) -> Result<(), I::Error> {
+        (**self).write(address, data)
+    }
+}
+
+/// An asynchronous serial bus giving access to the registers of an external device
+///
+/// This is the same as [`Interface`], for asynchronous bus drivers. The registers are then
+/// accessed with [`Bus::reg_async`].
+pub trait AsyncInterface {
+    /// The error returned by bus transfers
+    type Error;
+
+    /// Read `data.len()` bytes from the register at `address`
+    fn read(
+        &mut self,
+        address: &[u8],
+        data: &mut [u8],
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+
+    /// Write `data` to the register at `address`
+    fn write(
+        &mut self,
+        address: &[u8],
+        data: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+impl<I: AsyncInterface + ?Sized> AsyncInterface for &mut I {
+    type Error = I::Error;
+
+    #[inline]
+    fn read(
+        &mut self,
+        address: &[u8],
+        data: &mut [u8],
+    ) -> impl Future<Output = Result<(), I::Error>> {
+        (**self).read(address, data)
+    }
+
+    #[inline]
+    fn write(&mut self, address: &[u8], data: &[u8]) -> impl Future<Output = Result<(), I::Error>> {
+        (**self).write(address, data)
+    }
+}
+
+/// A register of an external device, accessed through a [`Bus`]
+///
+/// This trait is implemented by the [`periph!`] macro for the registers of peripherals declared
+/// with `bus`. These registers are transferred with their own width and the byte order of the bus.
+pub trait BusRegister: Register {}
+
+/// The registers of an external device, accessed through a serial bus
+///
+/// Registers of peripherals declared with `bus` in the [`periph!`] macro are accessed with
+/// [`Bus::reg`], or [`Bus::reg_async`] if the bus implements [`AsyncInterface`]. Their offset is
+/// used as the register address on the bus, and their width as the number of bytes transferred.
+/// Values and addresses are in the byte order of the bus. Many devices, e.g. the INA219 or the
+/// TMP102, have wide registers at consecutive addresses, which `bus` allows. All accesses return the error of the [`Interface`] if the transfer fails.
+///
+/// ```
+/// use peripherals::{periph, Bus, Endianness, Interface};
+///
+/// periph! {
+///     MySensor bus;
+///     rw CONFIG @ 0x00: u16 = 0x0000 {
+///         ENABLE: 0 = struct Enable(bool);
+///         RATE: 8..11 = struct Rate(u8);
+///     }
+///     r DATA @ 0x01: u16 = 0x0000 {}
+/// }
+///
+/// // A fake bus, with a one-byte address selecting a 16-bit register
+/// struct Registers([u16; 4]);
+///
+/// impl Interface for Registers {
+///     type Error = ();
+///
+///     fn read(&mut self, address: &[u8], data: &mut [u8]) -> Result<(), ()> {
+///         let register = self.0.get(address[0] as usize).ok_or(())?;
+///         data.copy_from_slice(&register.to_be_bytes());
+///         Ok(())
+///     }
+///
+///     fn write(&mut self, address: &[u8], data: &[u8]) -> Result<(), ()> {
+///         let register = self.0.get_mut(address[0] as usize).ok_or(())?;
+///         *register = u16::from_be_bytes([data[0], data[1]]);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut bus = Bus::new(Registers([0, 0x1234, 0, 0]), 1, Endianness::Big);
+/// bus.reg::<CONFIG>().modify(Enable(true) | Rate(5))?;
+/// assert_eq!(bus.reg::<CONFIG>().field(CONFIG::RATE)?, Rate(5));
+/// assert_eq!(bus.reg::<DATA>().read()?.value(), 0x1234);
+/// assert_eq!(bus.into_inner().0[..2], [0x0501, 0x1234]);
+/// # Ok::<(), ()>(())
+/// ```
+pub struct Bus<I> {
+    interface: I,
+    address_width: usize,
+    endianness: Endianness,
+}
+
+impl<I> Bus<I> {
+    /// Create a bus with the given address width, in bytes, and endianness
+    ///
+    /// # Panics
+    ///
+    /// Panics if the address width is not between 1 and 4 bytes.
+    #[inline]
+    pub const fn new(interface: I, address_width: usize, endianness: Endianness) -> Bus<I> {
+        if address_width == 0 || address_width > 4 {
+            panic!("invalid address width");
+        }
+        Bus {
+            interface,
+            address_width,
+            endianness,
+        }
+    }
+
+    /// Get the width of register addresses, in bytes
+    #[inline]
+    pub const fn address_width(&self) -> usize {
+        self.address_width
+    }
+
+    /// Get the byte order of register addresses and values
+    #[inline]
+    pub const fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Get a mutable reference to the bus interface
+    #[inline]
+    pub fn interface(&mut self) -> &mut I {
+        &mut self.interface
+    }
+
+    /// Get back the bus interface
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.interface
+    }
+
+    /// Access a register of the device
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset of the register doesn't fit in the address width.
+    #[inline]
+    pub fn reg<R: BusRegister>(&mut self) -> BusReg<'_, R, I> {
+        self.check_address::<R>();
+        BusReg {
+            bus: self,
+            _reg: PhantomData,
+        }
+    }
+
+    /// Access a register of the device through an asynchronous interface
+    ///
+    /// ```
+    /// # peripherals::periph!{
+    /// #     MySensor bus;
+    /// #     rw CONFIG @ 0x02: u16 = 0x0000 {
+    /// #         ENABLE: 0 = struct Enable(bool);
+    /// #     }
+    /// #     r STATUS @ 0x04: u8 = 0x00 {
+    /// #         READY: 0 = struct Ready(bool);
+    /// #     }
+    /// # }
+    /// use peripherals::{AsyncInterface, Bus};
+    ///
+    /// async fn enable<I: AsyncInterface>(bus: &mut Bus<I>) -> Result<bool, I::Error> {
+    ///     bus.reg_async::<CONFIG>().modify(Enable(true)).await?;
+    ///     bus.reg_async::<STATUS>().test(Ready(true)).await
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset of the register doesn't fit in the address width.
+    #[inline]
+    pub fn reg_async<R: BusRegister>(&mut self) -> AsyncBusReg<'_, R, I> {
+        self.check_address::<R>();
+        AsyncBusReg {
+            bus: self,
+            _reg: PhantomData,
+        }
+    }
+
+    /// Check that the offset of a register fits in the address width
+    fn check_address<R: Register>(&self) {
+        if R::OFFSET as u128 >> (8 * self.address_width) != 0 {
+            panic!("register address doesn't fit in the address width");
+        }
+    }
+
+    /// Encode `value` on `width` bytes
+    fn encode(&self, value: u128, width: usize, buf: &mut [u8; 16]) {
+        match self.endianness {
+            Endianness::Little => buf[..width].copy_from_slice(&value.to_le_bytes()[..width]),
+            Endianness::Big => buf[..width].copy_from_slice(&value.to_be_bytes()[16 - width..]),
+        }
+    }
+
+    /// Decode a value from `width` bytes
+    fn decode(&self, width: usize, buf: &[u8; 16]) -> u128 {
+        let mut bytes = [0; 16];
+        match self.endianness {
+            Endianness::Little => {
+                bytes[..width].copy_from_slice(&buf[..width]);
+                u128::from_le_bytes(bytes)
+            }
+            Endianness::Big => {
+                bytes[16 - width..].copy_from_slice(&buf[..width]);
+                u128::from_be_bytes(bytes)
+            }
+        }
+    }
+
+    /// Prepare a transfer reading a register
+    fn read_transfer<R: Register>(&self) -> Transfer {
+        let mut transfer = Transfer {
+            address: [0; 16],
+            address_width: self.address_width,
+            data: [0; 16],
+            width: <R::Int as Int>::WIDTH / 8,
+        };
+        self.encode(R::OFFSET as u128, self.address_width, &mut transfer.address);
+        transfer
+    }
+
+    /// Prepare a transfer writing a value to a register
+    fn write_transfer<R: Register>(&self, value: Value<R::Value>) -> Transfer {
+        let mut transfer = self.read_transfer::<R>();
+        self.encode(value.value().to_u128(), transfer.width, &mut transfer.data);
+        transfer
+    }
+
+    /// Decode the value read by a transfer
+    fn value<R: Register>(&self, transfer: &Transfer) -> Value<R::Value> {
+        let value = self.decode(transfer.width, &transfer.data);
+        unsafe { Value::from_raw(R::Int::from_u128(value)) }
+    }
+}
+
+/// The encoded address and data of a register transfer
+struct Transfer {
+    address: [u8; 16],
+    address_width: usize,
+    data: [u8; 16],
+    width: usize,
+}
+
+impl Transfer {
+    /// The address and data to write
+    fn write(&self) -> (&[u8], &[u8]) {
+        (
+            &self.address[..self.address_width],
+            &self.data[..self.width],
+        )
+    }
+
+    /// The address and the buffer for the data read
+    fn read(&mut self) -> (&[u8], &mut [u8]) {
+        (
+            &self.address[..self.address_width],
+            &mut self.data[..self.width],
+        )
+    }
+}
+
+impl<I: Debug> Debug for Bus<I> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Bus")
+            .field("interface", &self.interface)
+            .field("address_width", &self.address_width)
+            .field("endianness", &self.endianness)
+            .finish()
+    }
+}
+
+/// A register of a device accessed through a [`Bus`]
+///
+/// It has the same methods than the [`Reg`] type, returning the error of the bus [`Interface`].
+/// It is created by [`Bus::reg`].
+pub struct BusReg<'a, R, I> {
+    bus: &'a mut Bus<I>,
+    _reg: PhantomData<R>,
+}
+
+impl<R: ReadRegister, I: Interface> BusReg<'_, R, I> {
+    /// Read the current value of this register
+    ///
+    /// This returns a [`Value`], which can be used to read fields or modified and written back.
+    #[inline]
+    pub fn read(&mut self) -> Result<Value<R::Value>, I::Error> {
+        let mut transfer = self.bus.read_transfer::<R>();
+        let (address, data) = transfer.read();
+        self.bus.interface.read(address, data)?;
+        Ok(self.bus.value::<R>(&transfer))
+    }
+
+    /// Read the given field
+    ///
+    /// Same as `register.read()?.field(fields)`. See [`Value::field`] for more details.
+    #[inline]
+    pub fn field<T>(&mut self, field: Field<R::Value, T, R::Int>) -> Result<T, I::Error>
+    where
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        Ok(self.read()?.field(field))
+    }
+
+    /// Read the given fields
+    ///
+    /// Same as `register.read()? & fields`. See [`Value`] for more details.
+    #[inline]
+    pub fn fields<F: Into<Fields<R::Value>> + MayToggle>(
+        &mut self,
+        fields: F,
+    ) -> Result<FieldValues<R::Value, F::Toggle>, I::Error> {
+        Ok(self.read()? & fields)
+    }
+
+    /// Test the value of the given fields
+    ///
+    /// Same as `register.read()?.test(bits)`. See [`Value::test`] for more details.
+    #[inline]
+    pub fn test<B: Into<FieldValues<R::Value>>>(&mut self, bits: B) -> Result<bool, I::Error> {
+        Ok(self.read()?.test(bits))
+    }
+}
+
+impl<R: WriteRegister, I: Interface> BusReg<'_, R, I> {
+    /// Write a value to this register
+    ///
+    /// This takes a [`Value`], which can be read from a register or created with `Default`.
+    #[inline]
+    pub fn write(&mut self, value: Value<R::Value>) -> Result<(), I::Error> {
+        let transfer = self.bus.write_transfer::<R>(value);
+        let (address, data) = transfer.write();
+        self.bus.interface.write(address, data)
+    }
+
+    /// Reset this register
+    ///
+    /// This set the register to the value it has right after a reset of the device.
+    #[inline]
+    pub fn reset(&mut self) -> Result<(), I::Error> {
+        self.write(Value::reset())
+    }
+
+    /// Write a value computed from the reset value
+    ///
+    /// Same as [`Reg::write_with`].
+    #[inline]
+    pub fn write_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(
+        &mut self,
+        f: F,
+    ) -> Result<(), I::Error> {
+        self.write(f(Value::reset()))
+    }
+}
+
+impl<R: ReadRegister + WriteRegister, I: Interface> BusReg<'_, R, I> {
+    /// Modify the given fields
+    ///
+    /// Same as [`Reg::modify`]. The register is read then written, and the first error is
+    /// returned.
+    #[inline]
+    pub fn modify<B: Into<FieldValues<R::Value>>>(&mut self, bits: B) -> Result<(), I::Error> {
+        let value = self.read()? | bits;
+        self.write(value)
+    }
+
+    /// Modify this register with a closure
+    ///
+    /// Same as [`Reg::modify_with`].
+    #[inline]
+    pub fn modify_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(
+        &mut self,
+        f: F,
+    ) -> Result<(), I::Error> {
+        let value = f(self.read()?);
+        self.write(value)
+    }
+
+    /// Toggle the given fields
+    ///
+    /// Same as [`Reg::toggle`].
+    #[inline]
+    pub fn toggle<F: Into<Fields<R::Value, Toggle>>>(&mut self, fields: F) -> Result<(), I::Error> {
+        let fields = fields.into();
+        let value = self.read()? ^ fields;
+        self.write(value)
+    }
+}
+
+impl<R: Register, I> Debug for BusReg<'_, R, I> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "BusReg {} @ 0x{:02x}", R::NAME, R::OFFSET)
+    }
+}
+
+/// A register of a device accessed through a [`Bus`] with an asynchronous interface
+///
+/// It has the same methods than [`BusReg`], as `async` functions. It is created by
+/// [`Bus::reg_async`].
+pub struct AsyncBusReg<'a, R, I> {
+    bus: &'a mut Bus<I>,
+    _reg: PhantomData<R>,
+}
+
+impl<R: ReadRegister, I: AsyncInterface> AsyncBusReg<'_, R, I> {
+    /// Read the current value of this register
+    ///
+    /// This returns a [`Value`], which can be used to read fields or modified and written back.
+    #[inline]
+    pub async fn read(&mut self) -> Result<Value<R::Value>, I::Error> {
+        let mut transfer = self.bus.read_transfer::<R>();
+        let (address, data) = transfer.read();
+        self.bus.interface.read(address, data).await?;
+        Ok(self.bus.value::<R>(&transfer))
+    }
+
+    /// Read the given field
+    ///
+    /// Same as [`BusReg::field`].
+    #[inline]
+    pub async fn field<T>(&mut self, field: Field<R::Value, T, R::Int>) -> Result<T, I::Error>
+    where
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        Ok(self.read().await?.field(field))
+    }
+
+    /// Read the given fields
+    ///
+    /// Same as [`BusReg::fields`].
+    #[inline]
+    pub async fn fields<F: Into<Fields<R::Value>> + MayToggle>(
+        &mut self,
+        fields: F,
+    ) -> Result<FieldValues<R::Value, F::Toggle>, I::Error> {
+        Ok(self.read().await? & fields)
+    }
+
+    /// Test the value of the given fields
+    ///
+    /// Same as [`BusReg::test`].
+    #[inline]
+    pub async fn test<B: Into<FieldValues<R::Value>>>(
+        &mut self,
+        bits: B,
+    ) -> Result<bool, I::Error> {
+        Ok(self.read().await?.test(bits))
+    }
+}
+
+impl<R: WriteRegister, I: AsyncInterface> AsyncBusReg<'_, R, I> {
+    /// Write a value to this register
+    ///
+    /// This takes a [`Value`], which can be read from a register or created with `Default`.
+    #[inline]
+    pub async fn write(&mut self, value: Value<R::Value>) -> Result<(), I::Error> {
+        let transfer = self.bus.write_transfer::<R>(value);
+        let (address, data) = transfer.write();
+        self.bus.interface.write(address, data).await
+    }
+
+    /// Reset this register
+    ///
+    /// Same as [`BusReg::reset`].
+    #[inline]
+    pub async fn reset(&mut self) -> Result<(), I::Error> {
+        self.write(Value::reset()).await
+    }
+
+    /// Write a value computed from the reset value
+    ///
+    /// Same as [`BusReg::write_with`].
+    #[inline]
+    pub async fn write_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(
+        &mut self,
+        f: F,
+    ) -> Result<(), I::Error> {
+        self.write(f(Value::reset())).await
+    }
+}
+
+impl<R: ReadRegister + WriteRegister, I: AsyncInterface> AsyncBusReg<'_, R, I> {
+    /// Modify the given fields
+    ///
+    /// Same as [`BusReg::modify`].
+    #[inline]
+    pub async fn modify<B: Into<FieldValues<R::Value>>>(
+        &mut self,
+        bits: B,
+    ) -> Result<(), I::Error> {
+        let value = self.read().await? | bits;
+        self.write(value).await
+    }
+
+    /// Modify this register with a closure
+    ///
+    /// Same as [`BusReg::modify_with`].
+    #[inline]
+    pub async fn modify_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(
+        &mut self,
+        f: F,
+    ) -> Result<(), I::Error> {
+        let value = f(self.read().await?);
+        self.write(value).await
+    }
+
+    /// Toggle the given fields
+    ///
+    /// Same as [`BusReg::toggle`].
+    #[inline]
+    pub async fn toggle<F: Into<Fields<R::Value, Toggle>>>(
+        &mut self,
+        fields: F,
+    ) -> Result<(), I::Error> {
+        let fields = fields.into();
+        let value = self.read().await? ^ fields;
+        self.write(value).await
+    }
+}
+
+impl<R: Register, I> Debug for AsyncBusReg<'_, R, I> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "AsyncBusReg {} @ 0x{:02x}", R::NAME, R::OFFSET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock::poll;
+    use core::pin::pin;
+    use core::task::Poll;
+    use std::vec::Vec;
+
+    /// An in-memory bus, with 16-bit little-endian addresses, logging the transfers
+    struct FakeBus {
+        memory: [u8; 6],
+        log: Vec<(Vec<u8>, Vec<u8>)>,
+        fail: bool,
+    }
+
+    impl Interface for FakeBus {
+        type Error = &'static str;
+
+        fn read(&mut self, address: &[u8], data: &mut [u8]) -> Result<(), &'static str> {
+            if self.fail {
+                return Err("nack");
+            }
+            let start = address[0] as usize + ((address[1] as usize) << 8);
+            data.copy_from_slice(&self.memory[start..start + data.len()]);
+            Ok(())
+        }
+
+        fn write(&mut self, address: &[u8], data: &[u8]) -> Result<(), &'static str> {
+            if self.fail {
+                return Err("nack");
+            }
+            let start = address[0] as usize + ((address[1] as usize) << 8);
+            self.memory[start..start + data.len()].copy_from_slice(data);
+            self.log.push((address.to_vec(), data.to_vec()));
+            Ok(())
+        }
+    }
+
+    impl AsyncInterface for FakeBus {
+        type Error = &'static str;
+
+        async fn read(&mut self, address: &[u8], data: &mut [u8]) -> Result<(), &'static str> {
+            Interface::read(self, address, data)
+        }
+
+        async fn write(&mut self, address: &[u8], data: &[u8]) -> Result<(), &'static str> {
+            Interface::write(self, address, data)
+        }
+    }
+
+    /// The example peripheral, accessed through a bus
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod sensor {
+        crate::periph! {
+            Sensor bus;
+            rw CONFIG @ 0x00: u16 = 0x0000 {
+                MODE: 0..1 = enum Mode {
+                    A = 0,
+                    B = 1,
+                    C = 2,
+                    D = 3,
+                }
+                FLAG: 2 = enum CfgFlag {
+                    False = 0,
+                    True = 1,
+                }
+            }
+            r STATUS @ 0x02: u16 = 0x0000 {
+                STAT: 0..1 = enum Status {
+                    A = 0,
+                    B = 1,
+                    C = 2,
+                    D = 3,
+                }
+                FLAG: 2 = struct StatFlag(bool);
+            }
+            w BUFFER @ 0x04: u16 = 0x0000 {
+                DATA: 0..7 = struct Data(u8);
+            }
+        }
+    }
+
+    #[test]
+    fn bus() {
+        use sensor::{CfgFlag, Data, Mode, StatFlag, Status, BUFFER, CONFIG, STATUS};
+
+        let mut bus = Bus::new(
+            FakeBus {
+                memory: [0x00, 0x00, 0x06, 0x00, 0x00, 0x00],
+                log: Vec::new(),
+                fail: false,
+            },
+            2,
+            Endianness::Little,
+        );
+        assert_eq!(bus.reg::<STATUS>().field(STATUS::STAT), Ok(Status::C));
+        assert_eq!(bus.reg::<STATUS>().test(StatFlag(true)), Ok(true));
+        assert_eq!(bus.reg::<CONFIG>().modify(Mode::D | CfgFlag::True), Ok(()));
+        assert_eq!(
+            bus.reg::<BUFFER>().write(Value::reset() | Data(0xa5)),
+            Ok(())
+        );
+        assert_eq!(
+            bus.interface().log,
+            [
+                (std::vec![0x00, 0x00], std::vec![0x07, 0x00]),
+                (std::vec![0x04, 0x00], std::vec![0xa5, 0x00]),
+            ]
+        );
+
+        bus.interface().fail = true;
+        assert_eq!(bus.reg::<CONFIG>().toggle(CONFIG::FLAG), Err("nack"));
+        assert_eq!(bus.reg::<BUFFER>().reset(), Err("nack"));
+        assert_eq!(
+            bus.into_inner().memory,
+            [0x07, 0x00, 0x06, 0x00, 0xa5, 0x00]
+        );
+    }
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod current_sensor {
+        crate::periph! {
+            CurrentSensor bus;
+            rw CONFIG @ 0x00: u16 = 0x399f {
+                MODE: 0..2 = struct Mode(u8);
+            }
+            r SHUNT @ 0x01: u16 = 0x0000 {}
+            r VOLTAGE @ 0x02: u16 = 0x0000 {}
+            r ID @ 0x100: u16 = 0x0000 {}
+        }
+    }
+
+    /// A bus with 8-bit addresses, each selecting a 16-bit big-endian register
+    struct RegisterBus {
+        registers: [u16; 3],
+        log: Vec<(u8, usize)>,
+    }
+
+    impl Interface for RegisterBus {
+        type Error = ();
+
+        fn read(&mut self, address: &[u8], data: &mut [u8]) -> Result<(), ()> {
+            self.log.push((address[0], data.len()));
+            data.copy_from_slice(&self.registers[address[0] as usize].to_be_bytes());
+            Ok(())
+        }
+
+        fn write(&mut self, address: &[u8], data: &[u8]) -> Result<(), ()> {
+            self.log.push((address[0], data.len()));
+            self.registers[address[0] as usize] = u16::from_be_bytes([data[0], data[1]]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn consecutive_bus_registers() {
+        use current_sensor::{Mode, CONFIG, SHUNT, VOLTAGE};
+
+        let mut bus = Bus::new(
+            RegisterBus {
+                registers: [0x399f, 0x0123, 0x4567],
+                log: Vec::new(),
+            },
+            1,
+            Endianness::Big,
+        );
+        assert_eq!(bus.reg::<CONFIG>().modify(Mode(3)), Ok(()));
+        assert_eq!(
+            bus.reg::<SHUNT>().read().map(|value| value.value()),
+            Ok(0x0123)
+        );
+        assert_eq!(
+            bus.reg::<VOLTAGE>().read().map(|value| value.value()),
+            Ok(0x4567)
+        );
+        let bus = bus.into_inner();
+        assert_eq!(bus.registers, [0x399b, 0x0123, 0x4567]);
+        assert_eq!(bus.log, [(0x00, 2), (0x00, 2), (0x01, 2), (0x02, 2)]);
+    }
+
+    #[test]
+    #[should_panic = "register address doesn't fit in the address width"]
+    fn bus_address_too_large() {
+        let mut bus = Bus::new(
+            RegisterBus {
+                registers: [0; 3],
+                log: Vec::new(),
+            },
+            1,
+            Endianness::Big,
+        );
+        bus.reg::<current_sensor::ID>();
+    }
+
+    #[test]
+    fn async_bus() {
+        use sensor::{Mode, Status, BUFFER, CONFIG, STATUS};
+
+        let mut bus = Bus::new(
+            FakeBus {
+                memory: [0x00, 0x00, 0x06, 0x00, 0x00, 0x00],
+                log: Vec::new(),
+                fail: false,
+            },
+            2,
+            Endianness::Little,
+        );
+        let (field, _) = poll(pin!(bus.reg_async::<STATUS>().field(STATUS::STAT)));
+        assert_eq!(field, Poll::Ready(Ok(Status::C)));
+        let (modify, _) = poll(pin!(bus.reg_async::<CONFIG>().modify(Mode::D)));
+        assert_eq!(modify, Poll::Ready(Ok(())));
+        assert_eq!(
+            bus.interface().log,
+            [(std::vec![0x00, 0x00], std::vec![0x03, 0x00])]
+        );
+
+        bus.interface().fail = true;
+        let (write, _) = poll(pin!(bus.reg_async::<BUFFER>().reset()));
+        assert_eq!(write, Poll::Ready(Err("nack")));
+    }
+
+    #[test]
+    fn big_endian_bus() {
+        use sensor::{Mode, CONFIG};
+
+        let mut bus = Bus::new(
+            FakeBus {
+                memory: [0; 6],
+                log: Vec::new(),
+                fail: false,
+            },
+            2,
+            Endianness::Big,
+        );
+        assert_eq!(bus.reg::<CONFIG>().write(Value::reset() | Mode::B), Ok(()));
+        assert_eq!(
+            bus.into_inner().log,
+            [(std::vec![0x00, 0x00], std::vec![0x00, 0x01])]
+        );
+    }
+}
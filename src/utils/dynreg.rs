@@ -21,6 +21,11 @@ impl<R: Register> DynReg<R> {
     pub fn ptr_mut(&mut self) -> *mut R::Int {
         (self as *mut _ as usize + R::OFFSET) as *mut R::Int
     }
+
+    /// Accesses from the base address of the peripheral
+    fn pointer(&self) -> Pointer {
+        unsafe { Pointer::new(self as *const _ as usize) }
+    }
 }
 
 impl<R: ReadRegister> DynReg<R> {
@@ -29,7 +34,7 @@ impl<R: ReadRegister> DynReg<R> {
     /// This returns a [`Value`], which can be used to read fields or modified and written back.
     #[inline]
     pub fn read(&self) -> Value<R::Value> {
-        unsafe { Value::from_raw(self.ptr().read_volatile()) }
+        unsafe { Value::from_raw(read_register::<R, _>(&self.pointer(), R::OFFSET)) }
     }
 
     /// Read the given field
@@ -62,6 +67,72 @@ impl<R: ReadRegister> DynReg<R> {
     pub fn test<B: Into<FieldValues<R::Value>>>(&self, bits: B) -> bool {
         self.read().test(bits)
     }
+
+    /// Wait until the given fields have the given values
+    ///
+    /// This reads the register until [`Value::test`] returns `true`, and returns the last value
+    /// read. The [`Timeout`] is checked after each read, so that this doesn't hang forever if the
+    /// hardware misbehaves.
+    ///
+    /// ```
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     r STATUS @ 0x00: u16 = 0x0000 {
+    /// #         READY: 0 = struct Ready(bool);
+    /// #         BUSY: 1 = struct Busy(bool);
+    /// #     }
+    /// # }
+    /// use peripherals::TimedOut;
+    ///
+    /// # let mut memory = 0x0001_u16;
+    /// # let periph = unsafe { &*(&mut memory as *mut u16 as *const DynMyPeripheral) };
+    /// // Read the register at most 1000 times
+    /// let value = periph.status.wait_until(Ready(true), 1000);
+    /// assert_eq!(value.map(|value| value.value()), Ok(0x0001));
+    ///
+    /// // Wait until some deadline is reached
+    /// # let mut now = 0;
+    /// # let mut timer = || { now += 1; now };
+    /// let value = periph.status.wait_until(Ready(true) | Busy(true), || timer() > 10);
+    /// assert_eq!(value, Err(TimedOut));
+    /// ```
+    #[inline]
+    pub fn wait_until<B: Into<FieldValues<R::Value>>, T: Timeout>(
+        &self,
+        bits: B,
+        mut timeout: T,
+    ) -> Result<Value<R::Value>, TimedOut> {
+        let bits = bits.into();
+        loop {
+            let value = self.read();
+            if value.test(bits) {
+                return Ok(value);
+            } else if timeout.expired() {
+                return Err(TimedOut);
+            }
+        }
+    }
+
+    /// Wait while the given fields have the given values
+    ///
+    /// This is the opposite of [`DynReg::wait_until`]: it reads the register until [`Value::test`]
+    /// returns `false`.
+    #[inline]
+    pub fn wait_while<B: Into<FieldValues<R::Value>>, T: Timeout>(
+        &self,
+        bits: B,
+        mut timeout: T,
+    ) -> Result<Value<R::Value>, TimedOut> {
+        let bits = bits.into();
+        loop {
+            let value = self.read();
+            if !value.test(bits) {
+                return Ok(value);
+            } else if timeout.expired() {
+                return Err(TimedOut);
+            }
+        }
+    }
 }
 
 impl<R: WriteRegister> DynReg<R> {
@@ -70,9 +141,7 @@ impl<R: WriteRegister> DynReg<R> {
     /// This takes a [`Value`], which can be read from a register or created with `Default`.
     #[inline]
     pub fn write(&mut self, value: Value<R::Value>) {
-        unsafe {
-            self.ptr_mut().write_volatile(value.value());
-        }
+        write_register::<R, _>(&self.pointer(), R::OFFSET, value.value());
     }
 
     /// Reset this register
@@ -82,6 +151,15 @@ impl<R: WriteRegister> DynReg<R> {
     pub fn reset(&mut self) {
         self.write(Value::reset());
     }
+
+    /// Write a value computed from the reset value
+    ///
+    /// The closure receives the reset value of this register and returns the value to write. See
+    /// [`DynReg::modify_with`] for an example.
+    #[inline]
+    pub fn write_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(Value::reset()));
+    }
 }
 
 impl<R: ReadRegister + WriteRegister> DynReg<R> {
@@ -94,6 +172,45 @@ impl<R: ReadRegister + WriteRegister> DynReg<R> {
         self.write(self.read() | bits);
     }
 
+    /// Modify this register with a closure
+    ///
+    /// The closure receives the current value of this register and returns the value to write.
+    /// This allows to modify the register depending on its current value.
+    ///
+    /// ```
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     rw CONFIG @ 0x00: u16 = 0x0000 {
+    /// #         MODE: 0..1 = enum Mode {
+    /// #             A = 0,
+    /// #             B = 1,
+    /// #             C = 2,
+    /// #             D = 3,
+    /// #         }
+    /// #         IDLE: 2 = struct Idle(bool);
+    /// #         DATA: 8..15 = struct Data(u8);
+    /// #     }
+    /// # }
+    /// # let mut memory = 0x0304_u16;
+    /// # let periph = unsafe { &mut *(&mut memory as *mut u16 as *mut DynMyPeripheral) };
+    /// // Set `MODE` to `B` only if idle, and increment `DATA`
+    /// periph.config.modify_with(|mut value| {
+    ///     if value.test(Idle(true)) {
+    ///         value |= Mode::B;
+    ///     }
+    ///     value | Data(value.field(CONFIG::DATA).0 + 1)
+    /// });
+    /// assert_eq!(periph.config.read().value(), 0x0405);
+    ///
+    /// // Write a value computed from the reset value
+    /// periph.config.write_with(|value| value | Mode::C | Data(0x12));
+    /// assert_eq!(periph.config.read().value(), 0x1202);
+    /// ```
+    #[inline]
+    pub fn modify_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(self.read()));
+    }
+
     /// Toggle the given fields
     ///
     /// This takes a toggleable field defined for this register by the [`periph!`] macro. These
@@ -105,7 +222,65 @@ impl<R: ReadRegister + WriteRegister> DynReg<R> {
     }
 }
 
+#[cfg(feature = "critical-section")]
+impl<R: WriteRegister> DynReg<R> {
+    /// Write a value to this register from a shared reference
+    ///
+    /// Same as [`DynReg::write`], but inside a critical section.
+    #[inline]
+    pub fn write_cs(&self, value: Value<R::Value>) {
+        critical_section::with(|_| {
+            write_register::<R, _>(&self.pointer(), R::OFFSET, value.value());
+        });
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<R: ReadRegister + WriteRegister> DynReg<R> {
+    /// Modify the given fields from a shared reference
+    ///
+    /// Same as [`DynReg::modify`], but the read-modify-write is done inside a critical section. This
+    /// allows to share a register between the main loop and interrupt handlers.
+    ///
+    /// ```
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     rw CONFIG @ 0x00: u16 = 0x0000 {
+    /// #         FLAG: 0 = struct Flag(bool);
+    /// #     }
+    /// # }
+    /// # let mut memory = 0_u16;
+    /// # let periph = unsafe { &*(&mut memory as *mut u16 as *const DynMyPeripheral) };
+    /// let config = &periph.config;
+    ///
+    /// config.modify_cs(Flag(true));
+    /// assert_eq!(config.read().value(), 0x0001);
+    /// config.toggle_cs(CONFIG::FLAG);
+    /// assert_eq!(config.read().value(), 0x0000);
+    /// ```
+    #[inline]
+    pub fn modify_cs<B: Into<FieldValues<R::Value>>>(&self, bits: B) {
+        critical_section::with(|_| {
+            let value = self.read() | bits;
+            write_register::<R, _>(&self.pointer(), R::OFFSET, value.value());
+        });
+    }
+
+    /// Toggle the given fields from a shared reference
+    ///
+    /// Same as [`DynReg::toggle`], but the read-modify-write is done inside a critical section.
+    #[inline]
+    pub fn toggle_cs<F: Into<Fields<R::Value, Toggle>>>(&self, fields: F) {
+        let fields = fields.into();
+        critical_section::with(|_| {
+            let value = self.read() ^ fields;
+            write_register::<R, _>(&self.pointer(), R::OFFSET, value.value());
+        });
+    }
+}
+
 impl<R: Register> Debug for DynReg<R> {
+    #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(fmt, "DynReg {} @ 0x{:06p}", R::NAME, self.ptr())
     }
@@ -42,7 +42,6 @@ use super::*;
 /// value ^= Register::BIT2;
 /// assert_eq!(value.value(), 0b1010);
 /// ```
-
 pub struct Field<R, T, I> {
     mask: I,
     offset: usize,
@@ -50,19 +49,21 @@ pub struct Field<R, T, I> {
     _type: PhantomData<T>,
 }
 
-impl<R, T, I> Field<R, T, I> {
+impl<R, T, I: Copy> Field<R, T, I> {
     /// Get the raw mask
     #[inline]
-    pub fn mask(self) -> I {
+    pub const fn mask(self) -> I {
         self.mask
     }
 
     /// Get the field offset
     #[inline]
-    pub fn offset(self) -> usize {
+    pub const fn offset(self) -> usize {
         self.offset
     }
+}
 
+impl<R, T, I> Field<R, T, I> {
     /// Build from raw mask
     ///
     /// # Safety
@@ -77,34 +78,19 @@ impl<R, T, I> Field<R, T, I> {
             _type: PhantomData,
         }
     }
-
-    /// If this compiles, the `RegisterValue` trait can be added to the `Field` struct, and `R::Int`
-    /// can be used instead of a third type parameter `I`. As of rust 1.52.0, this requires the
-    /// `const_fn` feature flag.
-    /// ```compile_fail
-    /// const fn foo<T: Default>(t: T) -> T { t }
-    /// ```
-    /// ```
-    /// fn foo<T: Default>(t: T) {}
-    /// ```
-    fn _check_const_fn() {}
 }
 
 impl<R: RegisterValue, T> Clone for Field<R, T, R::Int> {
     #[inline]
     fn clone(&self) -> Field<R, T, R::Int> {
-        Field {
-            mask: self.mask,
-            offset: self.offset,
-            _reg: PhantomData,
-            _type: PhantomData,
-        }
+        *self
     }
 }
 
 impl<R: RegisterValue, T> Copy for Field<R, T, R::Int> {}
 
 impl<R: RegisterValue, T> Debug for Field<R, T, R::Int> {
+    #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         if fmt.alternate() {
             write!(
@@ -194,3 +180,71 @@ where
         unsafe { Fields::from_raw(self.mask ^ other.mask()) }
     }
 }
+
+/// Name and position of a register field
+///
+/// The fields of a register are listed in [`RegisterValue::FIELDS`] by the [`register!`] and
+/// [`periph!`] macros. This allows e.g. to name the fields that differ between two snapshots of a
+/// peripheral.
+///
+/// ```
+/// use peripherals::{register, RegisterValue};
+///
+/// register! {
+///     Register: u8 = 0b1001 {
+///         BIT1: 0 = struct Bit1(bool);
+///         TWO_BITS: 2..3 = struct TwoBits(u8);
+///     }
+/// }
+///
+/// let fields = Register::FIELDS;
+/// assert_eq!(fields.len(), 2);
+/// assert_eq!(fields[1].name(), "TWO_BITS");
+/// assert_eq!(fields[1].mask(), 0b1100);
+/// assert_eq!(fields[1].offset(), 2);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    name: &'static str,
+    mask: u128,
+    offset: usize,
+}
+
+impl FieldInfo {
+    /// Create the description of a field
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new(name: &'static str, mask: u128, offset: usize) -> FieldInfo {
+        FieldInfo { name, mask, offset }
+    }
+
+    /// Get the name of the field
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        self.name
+    }
+
+    /// Get the raw mask of the field
+    #[inline]
+    pub const fn mask(self) -> u128 {
+        self.mask
+    }
+
+    /// Get the field offset
+    #[inline]
+    pub const fn offset(self) -> usize {
+        self.offset
+    }
+}
+
+/// Check that the value of a field can be converted to its type
+#[doc(hidden)]
+#[inline]
+pub fn field_is_valid<R: RegisterValue, T>(field: Field<R, T, R::Int>, value: R::Int) -> bool
+where
+    R::Int: TryInto<T>,
+{
+    ((value & field.mask()) >> field.offset())
+        .try_into()
+        .is_ok()
+}
@@ -44,7 +44,6 @@ use super::*;
 /// value |= values;
 /// assert_eq!(value.value(), 0b0111);
 /// ```
-
 pub struct FieldValues<R: RegisterValue, T = ()> {
     bits: R::Int,
     mask: R::Int,
@@ -55,19 +54,19 @@ pub struct FieldValues<R: RegisterValue, T = ()> {
 impl<R: RegisterValue, T> FieldValues<R, T> {
     /// Get the raw bits to set
     #[inline]
-    pub fn bits(self) -> R::Int {
+    pub const fn bits(self) -> R::Int {
         self.bits
     }
 
     /// Get the raw mask
     #[inline]
-    pub fn mask(self) -> R::Int {
+    pub const fn mask(self) -> R::Int {
         self.mask
     }
 
     /// Get the fields specified in this value
     #[inline]
-    pub fn fields(self) -> Fields<R, T> {
+    pub const fn fields(self) -> Fields<R, T> {
         unsafe { Fields::from_raw(self.mask) }
     }
 
@@ -77,7 +76,7 @@ impl<R: RegisterValue, T> FieldValues<R, T> {
     ///
     /// You must ensure the value and mask are valid for the fields of the associated register.
     #[inline]
-    pub unsafe fn from_raw(bits: R::Int, mask: R::Int) -> FieldValues<R, T> {
+    pub const unsafe fn from_raw(bits: R::Int, mask: R::Int) -> FieldValues<R, T> {
         FieldValues {
             bits,
             mask,
@@ -101,12 +100,7 @@ impl<R: RegisterValue, T> FieldValues<R, T> {
 impl<R: RegisterValue, T> Clone for FieldValues<R, T> {
     #[inline]
     fn clone(&self) -> FieldValues<R, T> {
-        FieldValues {
-            bits: self.bits,
-            mask: self.mask,
-            _reg: PhantomData,
-            _toggle: PhantomData,
-        }
+        *self
     }
 }
 
@@ -125,6 +119,7 @@ impl<R: RegisterValue, T> Default for FieldValues<R, T> {
 }
 
 impl<R: RegisterValue, T> Debug for FieldValues<R, T> {
+    #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         if fmt.alternate() {
             write!(
@@ -61,7 +61,6 @@ use super::*;
 /// value |= value_12;
 /// assert_eq!(value.value(), 0b1001);
 /// ```
-
 pub struct Fields<R: RegisterValue, T = ()> {
     mask: R::Int,
     _reg: PhantomData<R>,
@@ -71,7 +70,7 @@ pub struct Fields<R: RegisterValue, T = ()> {
 impl<R: RegisterValue, T> Fields<R, T> {
     /// Get the raw mask
     #[inline]
-    pub fn mask(self) -> R::Int {
+    pub const fn mask(self) -> R::Int {
         self.mask
     }
 
@@ -81,7 +80,7 @@ impl<R: RegisterValue, T> Fields<R, T> {
     ///
     /// You should ensure the mask is valid for the fields of the associated register.
     #[inline]
-    pub unsafe fn from_raw(mask: R::Int) -> Fields<R, T> {
+    pub const unsafe fn from_raw(mask: R::Int) -> Fields<R, T> {
         Fields {
             mask,
             _reg: PhantomData,
@@ -103,11 +102,7 @@ impl<R: RegisterValue, T> Fields<R, T> {
 impl<R: RegisterValue, T> Clone for Fields<R, T> {
     #[inline]
     fn clone(&self) -> Fields<R, T> {
-        Fields {
-            mask: self.mask,
-            _reg: PhantomData,
-            _type: PhantomData,
-        }
+        *self
     }
 }
 
@@ -125,6 +120,7 @@ impl<R: RegisterValue, T> Default for Fields<R, T> {
 }
 
 impl<R: RegisterValue, T> Debug for Fields<R, T> {
+    #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         if fmt.alternate() {
             write!(
@@ -0,0 +1,282 @@
+use super::*;
+
+impl<R: FifoRegister + WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Write each value of an iterator to this register
+    ///
+    /// Each item is written over the reset value, as with [`Reg::write_with`], at the same
+    /// address. This allows e.g. to push a buffer to a transmit FIFO:
+    ///
+    /// ```no_run
+    /// # peripherals::periph!{
+    /// #     Uart;
+    /// #     r SR @ 0x00: u32 = 0x0000 {
+    /// #         TXE: 7 = struct Txe(bool);
+    /// #     }
+    /// #     rw DR @ 0x04 fifo: u32 = 0x0000 {
+    /// #         DATA: 0..7 = struct Data(u8);
+    /// #     }
+    /// # }
+    /// # peripherals::device!{
+    /// #     MyMicrocontroller;
+    /// #     UART @ 0x4001_1000: Uart;
+    /// # }
+    /// let mut uart = MyMicrocontroller::take().unwrap().uart;
+    /// uart.dr.write_iter(b"hello".iter().map(|&byte| Data(byte)));
+    ///
+    /// // Only write while the transmit register is empty
+    /// let (sr, dr) = (&uart.sr, &mut uart.dr);
+    /// let mut bytes = b"world".iter().map(|&byte| Data(byte));
+    /// let mut count = 0;
+    /// while count < 5 {
+    ///     count += dr.write_iter_while(|| sr.test(Txe(true)), bytes.by_ref());
+    /// }
+    /// ```
+    #[inline]
+    pub fn write_iter<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<FieldValues<R::Value>>,
+    {
+        for value in values {
+            self.write(Value::reset() | value);
+        }
+    }
+
+    /// Write values of an iterator to this register while `ready` returns `true`
+    ///
+    /// `ready` is called before taking each item from the iterator, e.g. to test a status field.
+    /// This returns the number of values written. Pass `iterator.by_ref()` to keep the remaining
+    /// values.
+    #[inline]
+    pub fn write_iter_while<F, I>(&mut self, mut ready: F, values: I) -> usize
+    where
+        F: FnMut() -> bool,
+        I: IntoIterator,
+        I::Item: Into<FieldValues<R::Value>>,
+    {
+        let mut values = values.into_iter();
+        let mut count = 0;
+        while ready() {
+            match values.next() {
+                Some(value) => self.write(Value::reset() | value),
+                None => break,
+            }
+            count += 1;
+        }
+        count
+    }
+}
+
+impl<R: FifoRegister + ReadRegister, P: Peripheral> Reg<R, P> {
+    /// Read the given field into each element of a buffer
+    ///
+    /// This reads this register once for each element of `buffer`, at the same address, e.g. to
+    /// pop values from a receive FIFO.
+    #[inline]
+    pub fn read_into<T>(&mut self, field: Field<R::Value, T, R::Int>, buffer: &mut [T])
+    where
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        for element in buffer {
+            *element = self.field(field);
+        }
+    }
+
+    /// Read the given field into a buffer while `ready` returns `true`
+    ///
+    /// `ready` is called before each read, e.g. to test a status field. This returns the number of
+    /// elements read, at the start of `buffer`.
+    #[inline]
+    pub fn read_into_while<F, T>(
+        &mut self,
+        mut ready: F,
+        field: Field<R::Value, T, R::Int>,
+        buffer: &mut [T],
+    ) -> usize
+    where
+        F: FnMut() -> bool,
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        let mut count = 0;
+        for element in buffer {
+            if !ready() {
+                break;
+            }
+            *element = self.field(field);
+            count += 1;
+        }
+        count
+    }
+}
+
+impl<R: FifoRegister + WriteRegister> DynReg<R> {
+    /// Write each value of an iterator to this register
+    ///
+    /// Same as [`Reg::write_iter`].
+    #[inline]
+    pub fn write_iter<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<FieldValues<R::Value>>,
+    {
+        for value in values {
+            self.write(Value::reset() | value);
+        }
+    }
+
+    /// Write values of an iterator to this register while `ready` returns `true`
+    ///
+    /// Same as [`Reg::write_iter_while`].
+    #[inline]
+    pub fn write_iter_while<F, I>(&mut self, mut ready: F, values: I) -> usize
+    where
+        F: FnMut() -> bool,
+        I: IntoIterator,
+        I::Item: Into<FieldValues<R::Value>>,
+    {
+        let mut values = values.into_iter();
+        let mut count = 0;
+        while ready() {
+            match values.next() {
+                Some(value) => self.write(Value::reset() | value),
+                None => break,
+            }
+            count += 1;
+        }
+        count
+    }
+}
+
+impl<R: FifoRegister + ReadRegister> DynReg<R> {
+    /// Read the given field into each element of a buffer
+    ///
+    /// Same as [`Reg::read_into`].
+    #[inline]
+    pub fn read_into<T>(&mut self, field: Field<R::Value, T, R::Int>, buffer: &mut [T])
+    where
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        for element in buffer {
+            *element = self.field(field);
+        }
+    }
+
+    /// Read the given field into a buffer while `ready` returns `true`
+    ///
+    /// Same as [`Reg::read_into_while`].
+    #[inline]
+    pub fn read_into_while<F, T>(
+        &mut self,
+        mut ready: F,
+        field: Field<R::Value, T, R::Int>,
+        buffer: &mut [T],
+    ) -> usize
+    where
+        F: FnMut() -> bool,
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        let mut count = 0;
+        for element in buffer {
+            if !ready() {
+                break;
+            }
+            *element = self.field(field);
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+    use std::vec::Vec;
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod fifo {
+        crate::periph! {
+            Fifo;
+            rw DATA @ 0x00 fifo: u16 = 0x0000 {
+                BYTE: 0..7 = struct Byte(u8);
+            }
+            rw CONTROL @ 0x04: u16 = 0x0000 {}
+            r STREAM @ 0x08 little_endian fifo: u16 = 0x0000 {}
+        }
+    }
+
+    #[test]
+    fn fifo() {
+        use fifo::{Byte, DATA};
+
+        let mut data = unsafe { Reg::<DATA, Mock<PERIPH>>::steal() };
+        data.write_iter((1..=3).map(Byte));
+        assert_eq!(mock::get::<DATA, PERIPH>().value(), 0x0003);
+        let mut values = (4..=6).map(Byte);
+        let mut ready = 2;
+        let written = data.write_iter_while(
+            || {
+                ready -= 1;
+                ready >= 0
+            },
+            values.by_ref(),
+        );
+        assert_eq!(written, 2);
+        assert_eq!(values.next(), Some(Byte(6)));
+        let writes = (1..=5).map(|value| Access::Write {
+            address: 0x4001_0000,
+            width: 2,
+            value,
+        });
+        assert_eq!(mock::take_log(), writes.collect::<Vec<_>>());
+
+        let mut reads = 0;
+        mock::on_read::<DATA, PERIPH, _>(move |value| {
+            reads += 1;
+            value | Byte(reads)
+        });
+        let mut buffer = [Byte(0); 4];
+        data.read_into(DATA::BYTE, &mut buffer[..2]);
+        let read = data.read_into_while(|| true, DATA::BYTE, &mut buffer[2..]);
+        assert_eq!(read, 2);
+        assert_eq!(buffer, [Byte(1), Byte(2), Byte(3), Byte(4)]);
+        assert_eq!(data.read_into_while(|| false, DATA::BYTE, &mut buffer), 0);
+        let reads = (1..=4).map(|value| Access::Read {
+            address: 0x4001_0000,
+            width: 2,
+            value,
+        });
+        assert_eq!(mock::take_log(), reads.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn snapshot_without_fifo() {
+        use fifo::{Fifo, CONTROL};
+
+        let mut periph = unsafe { Fifo::<Mock<PERIPH>>::steal() };
+        mock::set::<CONTROL, PERIPH>(0x0012);
+        let saved = periph.snapshot();
+        assert_eq!(saved.control.value(), 0x0012);
+        assert_eq!(saved.diff(&saved).count(), 0);
+        periph.restore(&saved);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Read {
+                    address: 0x4001_0004,
+                    width: 2,
+                    value: 0x0012,
+                },
+                Access::Write {
+                    address: 0x4001_0004,
+                    width: 2,
+                    value: 0x0012,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use super::*;
+
+/// Registers accessed indirectly, through an index register and a data register
+///
+/// Some peripherals give access to a secondary register map by writing the index of a register
+/// to an index register, then reading or writing a data register, e.g. Ethernet PHYs over MDIO
+/// or VGA controllers. The secondary registers are defined with the [`periph!`] macro, and
+/// accessed with [`Indirect::reg`]. The index is written to a field of the index register, and is
+/// the offset of the register divided by its width, so that e.g. 16-bit registers at offsets
+/// `0x00`, `0x02` and `0x04` have indexes 0, 1 and 2. The value of the data register is truncated
+/// or zero-extended to the width of the register.
+///
+/// Each access modifies the index field, leaving the other fields of the index register unchanged,
+/// then accesses the data register. Both registers are owned by this struct, and accesses borrow it
+/// mutably, so that no other access can happen between them.
+///
+/// ```no_run
+/// # peripherals::periph!{
+/// #     Mdio;
+/// #     rw ADDRESS @ 0x00: u32 = 0x0000 {
+/// #         REG: 0..4 = struct RegAddr(u8);
+/// #         PHY: 8..12 = struct PhyAddr(u8);
+/// #     }
+/// #     rw DATA @ 0x04: u32 = 0x0000 {}
+/// # }
+/// # peripherals::device!{
+/// #     MyMicrocontroller;
+/// #     MDIO @ 0x4002_8000: Mdio;
+/// # }
+/// use peripherals::Indirect;
+///
+/// mod phy {
+///     peripherals::periph! {
+///         Phy;
+///         rw BMCR @ 0x00: u16 = 0x0000 {
+///             RESET: 15 = struct Reset(bool);
+///         }
+///         r BMSR @ 0x02: u16 = 0x0000 {
+///             LINK: 2 = struct Link(bool);
+///         }
+///     }
+/// }
+///
+/// let mut mdio = MyMicrocontroller::take().unwrap().mdio;
+/// mdio.address.modify(PhyAddr(1));
+/// let mut phy = Indirect::new(mdio.address, ADDRESS::REG, mdio.data);
+///
+/// // Sets REG to 0 in ADDRESS, reads DATA and writes DATA
+/// phy.reg::<phy::BMCR>().modify(phy::Reset(true));
+/// // Sets REG to 1 in ADDRESS, then reads DATA
+/// let link = phy.reg::<phy::BMSR>().test(phy::Link(true));
+/// ```
+pub struct Indirect<I: Register, D: Register, P> {
+    index: Reg<I, P>,
+    mask: I::Int,
+    offset: usize,
+    data: Reg<D, P>,
+}
+
+impl<I: ReadRegister + WriteRegister, D: Register, P: Peripheral> Indirect<I, D, P> {
+    /// Access registers through the given field of the index register and the data register
+    #[inline]
+    pub const fn new<T>(
+        index: Reg<I, P>,
+        field: Field<I::Value, T, I::Int>,
+        data: Reg<D, P>,
+    ) -> Indirect<I, D, P> {
+        Indirect {
+            index,
+            mask: field.mask(),
+            offset: field.offset(),
+            data,
+        }
+    }
+
+    /// Get back the index and data registers
+    #[inline]
+    pub const fn into_inner(self) -> (Reg<I, P>, Reg<D, P>) {
+        (self.index, self.data)
+    }
+
+    /// Access a register of the secondary register map
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index of the register doesn't fit in the index field.
+    #[inline]
+    pub fn reg<R: Register>(&mut self) -> IndirectReg<'_, R, I, D, P> {
+        let index = R::OFFSET / (<R::Int as Int>::WIDTH / 8);
+        if index as u128 > self.mask.to_u128() >> self.offset {
+            panic!("register index doesn't fit in the index field");
+        }
+        IndirectReg {
+            indirect: self,
+            _reg: PhantomData,
+        }
+    }
+
+    /// Select the given register, modifying the index field only
+    fn select<R: Register>(&mut self) {
+        let index = R::OFFSET / (<R::Int as Int>::WIDTH / 8);
+        let index = I::Int::from_u128((index as u128) << self.offset);
+        let mask = self.mask;
+        self.index
+            .modify_with(|value| unsafe { Value::from_raw(value.value() & !mask | index) });
+    }
+
+    /// Read the data register, after selecting a register
+    fn read_data<R: Register>(&self) -> Value<R::Value>
+    where
+        D: ReadRegister,
+    {
+        let value = self.data.read().value().to_u128();
+        unsafe { Value::from_raw(R::Int::from_u128(value)) }
+    }
+
+    /// Write the data register, after selecting a register
+    fn write_data<R: Register>(&mut self, value: Value<R::Value>)
+    where
+        D: WriteRegister,
+    {
+        let value = D::Int::from_u128(value.value().to_u128());
+        self.data.write(unsafe { Value::from_raw(value) });
+    }
+}
+
+impl<I: Register, D: Register, P: Peripheral> Debug for Indirect<I, D, P> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Indirect")
+            .field("index", &self.index)
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// A register accessed indirectly, through an index register and a data register
+///
+/// It has the same methods than the [`Reg`] type, which take it by mutable reference. It is
+/// created by [`Indirect::reg`].
+pub struct IndirectReg<'a, R, I: Register, D: Register, P> {
+    indirect: &'a mut Indirect<I, D, P>,
+    _reg: PhantomData<R>,
+}
+
+impl<R, I, D, P> IndirectReg<'_, R, I, D, P>
+where
+    R: ReadRegister,
+    I: ReadRegister + WriteRegister,
+    D: ReadRegister,
+    P: Peripheral,
+{
+    /// Read the current value of this register
+    ///
+    /// This modifies the index register, then reads the data register.
+    #[inline]
+    pub fn read(&mut self) -> Value<R::Value> {
+        self.indirect.select::<R>();
+        self.indirect.read_data::<R>()
+    }
+
+    /// Read the given field
+    ///
+    /// Same as `register.read().field(fields)`. See [`Value::field`] for more details.
+    #[inline]
+    pub fn field<T>(&mut self, field: Field<R::Value, T, R::Int>) -> T
+    where
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        self.read().field(field)
+    }
+
+    /// Read the given fields
+    ///
+    /// Same as `register.read() & fields`. See [`Value`] for more details.
+    #[inline]
+    pub fn fields<F: Into<Fields<R::Value>> + MayToggle>(
+        &mut self,
+        fields: F,
+    ) -> FieldValues<R::Value, F::Toggle> {
+        self.read() & fields
+    }
+
+    /// Test the value of the given fields
+    ///
+    /// Same as `register.read().test(bits)`. See [`Value::test`] for more details.
+    #[inline]
+    pub fn test<B: Into<FieldValues<R::Value>>>(&mut self, bits: B) -> bool {
+        self.read().test(bits)
+    }
+}
+
+impl<R, I, D, P> IndirectReg<'_, R, I, D, P>
+where
+    R: WriteRegister,
+    I: ReadRegister + WriteRegister,
+    D: WriteRegister,
+    P: Peripheral,
+{
+    /// Write a value to this register
+    ///
+    /// This modifies the index register, then writes the data register.
+    #[inline]
+    pub fn write(&mut self, value: Value<R::Value>) {
+        self.indirect.select::<R>();
+        self.indirect.write_data::<R>(value);
+    }
+
+    /// Reset this register
+    ///
+    /// This set the register to the value it has right after a reset or a boot.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.write(Value::reset());
+    }
+
+    /// Write a value computed from the reset value
+    ///
+    /// Same as [`Reg::write_with`].
+    #[inline]
+    pub fn write_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(Value::reset()));
+    }
+}
+
+impl<R, I, D, P> IndirectReg<'_, R, I, D, P>
+where
+    R: ReadRegister + WriteRegister,
+    I: ReadRegister + WriteRegister,
+    D: ReadRegister + WriteRegister,
+    P: Peripheral,
+{
+    /// Modify the given fields
+    ///
+    /// Same as [`Reg::modify`]. The index register is modified once, before reading and writing
+    /// the data register.
+    #[inline]
+    pub fn modify<B: Into<FieldValues<R::Value>>>(&mut self, bits: B) {
+        self.modify_with(|value| value | bits);
+    }
+
+    /// Modify this register with a closure
+    ///
+    /// Same as [`Reg::modify_with`].
+    #[inline]
+    pub fn modify_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.indirect.select::<R>();
+        let value = f(self.indirect.read_data::<R>());
+        self.indirect.write_data::<R>(value);
+    }
+
+    /// Toggle the given fields
+    ///
+    /// Same as [`Reg::toggle`].
+    #[inline]
+    pub fn toggle<F: Into<Fields<R::Value, Toggle>>>(&mut self, fields: F) {
+        let fields = fields.into();
+        self.modify_with(|value| value ^ fields);
+    }
+}
+
+impl<R: Register, I: Register, D: Register, P: Peripheral> Debug for IndirectReg<'_, R, I, D, P> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "IndirectReg {} through {}.{}",
+            R::NAME,
+            P::NAME,
+            D::NAME
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{Data, Mode, StatFlag, BUFFER, CONFIG, STATUS};
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+    use crate::utils::Value;
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod mdio {
+        crate::periph! {
+            Mdio;
+            rw ADDRESS @ 0x00: u16 = 0x0000 {
+                REG: 0..4 = struct RegAddr(u8);
+                PHY: 8..12 = struct PhyAddr(u8);
+            }
+            rw DATA @ 0x02: u16 = 0x0000 {}
+        }
+
+        crate::periph! {
+            Phy;
+            rw EXTENDED @ 0x40: u16 = 0x0000 {}
+        }
+    }
+
+    #[test]
+    fn indirect() {
+        use mdio::{Mdio, PhyAddr, ADDRESS, DATA};
+
+        let mut mdio = unsafe { Mdio::<Mock<PERIPH>>::steal() };
+        mdio.address.modify(PhyAddr(3));
+        mock::set::<DATA, PERIPH>(0x0004);
+        mock::take_log();
+
+        let mut indirect = Indirect::new(mdio.address, ADDRESS::REG, mdio.data);
+        assert_eq!(indirect.reg::<STATUS>().field(STATUS::FLAG), StatFlag(true));
+        indirect.reg::<CONFIG>().modify(Mode::B);
+        indirect.reg::<BUFFER>().write(Value::reset() | Data(0x42));
+        // The PHY field of the index register is kept
+        assert_eq!(mock::get::<ADDRESS, PERIPH>().value(), 0x0302);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0300,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0301,
+                },
+                Access::Read {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x0004,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0301,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0300,
+                },
+                Access::Read {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x0004,
+                },
+                Access::Write {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x0005,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0300,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0302,
+                },
+                Access::Write {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x0042,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic = "register index doesn't fit in the index field"]
+    fn indirect_index_too_large() {
+        use mdio::{Mdio, ADDRESS, EXTENDED};
+
+        let mdio = unsafe { Mdio::<Mock<PERIPH>>::steal() };
+        let mut indirect = Indirect::new(mdio.address, ADDRESS::REG, mdio.data);
+        indirect.reg::<EXTENDED>();
+    }
+}
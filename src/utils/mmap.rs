@@ -0,0 +1,241 @@
+//! A backend to access peripherals from Linux userspace
+//!
+//! On SoCs running Linux, peripherals can be accessed from userspace by mapping a file in memory:
+//! - `/dev/mem`, at the physical address of the peripheral (see [`Mapping::dev_mem`]).
+//! - `/dev/uioN`, for devices exposed through the UIO framework (see [`Mapping::uio`]).
+//! - `/sys/bus/pci/devices/*/resourceN`, for PCI BARs (see [`Mapping::pci_resource`]).
+//!
+//! The base address of such a mapping is only known at runtime, so the `Dyn` peripheral types
+//! generated by the [`periph!`](crate::periph) macro are used. They are obtained from the mapping
+//! with [`Mapping::block`], or [`Mapping::steal_block`] for several peripherals of the same
+//! mapping, which check that the register block is aligned and fits in the mapping, and borrow
+//! the mapping so that it isn't unmapped while in use.
+//!
+//! This module is only available with the `std` feature, on unix targets.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use peripherals::mmap::Mapping;
+//! use peripherals::periph;
+//!
+//! periph! {
+//!     MyPeripheral;
+//!     rw CONFIG @ 0x00: u32 = 0x0000 {
+//!         ENABLE: 0 = struct Enable(bool);
+//!     }
+//! }
+//!
+//! let mut mapping = Mapping::uio(0, 0)?;
+//! let periph: &mut DynMyPeripheral = mapping.block(0x100)?;
+//! periph.config.modify(Enable(true));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use super::*;
+use memmap2::{MmapOptions, MmapRaw};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Error, ErrorKind};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+/// A file mapped in memory, containing peripherals
+///
+/// The file is unmapped when this is dropped.
+#[derive(Debug)]
+pub struct Mapping {
+    map: MmapRaw,
+}
+
+impl Mapping {
+    /// Map `len` bytes of a file, starting at `offset`
+    ///
+    /// The offset doesn't need to be aligned to a page.
+    #[inline]
+    pub fn open<F: AsRef<Path>>(path: F, offset: u64, len: usize) -> io::Result<Mapping> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Mapping::map(&file, offset, len)
+    }
+
+    /// Map `len` bytes of physical memory, starting at `address`, through `/dev/mem`
+    ///
+    /// The file is opened with `O_SYNC`, so that accesses are not cached. This usually requires
+    /// root privileges.
+    #[inline]
+    pub fn dev_mem(address: u64, len: usize) -> io::Result<Mapping> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_SYNC)
+            .open("/dev/mem")?;
+        Mapping::map(&file, address, len)
+    }
+
+    /// Map the memory region `map` of the UIO device `/dev/uio{device}`
+    ///
+    /// The size of the region is read from `/sys/class/uio/uio{device}/maps/map{map}/size`.
+    #[inline]
+    pub fn uio(device: usize, map: usize) -> io::Result<Mapping> {
+        let size = fs::read_to_string(std::format!(
+            "/sys/class/uio/uio{}/maps/map{}/size",
+            device,
+            map
+        ))?;
+        let size = size.trim();
+        let len = usize::from_str_radix(size.trim_start_matches("0x"), 16)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "invalid UIO map size"))?;
+
+        // The region `map` is selected by mapping at `map` pages
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        Mapping::open(std::format!("/dev/uio{}", device), map as u64 * page, len)
+    }
+
+    /// Map a PCI BAR through its resource file, e.g. `/sys/bus/pci/devices/0000:01:00.0/resource0`
+    ///
+    /// The whole BAR is mapped.
+    #[inline]
+    pub fn pci_resource<F: AsRef<Path>>(path: F) -> io::Result<Mapping> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len() as usize;
+        Mapping::map(&file, 0, len)
+    }
+
+    fn map(file: &File, offset: u64, len: usize) -> io::Result<Mapping> {
+        let map = MmapOptions::new().offset(offset).len(len).map_raw(file)?;
+        Ok(Mapping { map })
+    }
+
+    /// Get the address of the start of the mapping
+    #[inline]
+    pub fn base(&self) -> usize {
+        self.map.as_ptr() as usize
+    }
+
+    /// Get the length of the mapping, in bytes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Check whether the mapping is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.len() == 0
+    }
+
+    /// Get the register block at `offset` in the mapping
+    ///
+    /// This is a `Dyn` peripheral generated by the [`periph!`](crate::periph) macro. This returns
+    /// an error of kind `InvalidInput` if the register block is misaligned or doesn't fit in the
+    /// mapping. The mapping is borrowed mutably, see [`Mapping::steal_block`] to use several
+    /// register blocks at the same time.
+    ///
+    /// The peripheral struct always accesses the address of its instance, and is rejected:
+    ///
+    /// ```compile_fail
+    /// use peripherals::mmap::Mapping;
+    ///
+    /// peripherals::periph! {
+    ///     MyPeripheral;
+    ///     rw CONFIG @ 0x00: u32 = 0x0000 {}
+    /// }
+    /// peripherals::device! {
+    ///     MyDevice;
+    ///     PERIPH @ 0x4000_0000: MyPeripheral;
+    /// }
+    ///
+    /// let mut mapping = Mapping::uio(0, 0)?;
+    /// let periph: &mut MyPeripheral<PERIPH> = mapping.block(0x100)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    pub fn block<B: DynBlock>(&mut self, offset: usize) -> io::Result<&mut B> {
+        unsafe { self.steal_block(offset) }
+    }
+
+    /// Unconditionally get the register block at `offset` in the mapping
+    ///
+    /// Unlike [`Mapping::block`], this only borrows the mapping immutably, so that several
+    /// peripherals of the same mapping can be used at the same time. This returns the same errors
+    /// as [`Mapping::block`].
+    ///
+    /// ```no_run
+    /// # peripherals::periph!{
+    /// #     Uart;
+    /// #     rw CONFIG @ 0x00: u32 = 0x0000 {}
+    /// # }
+    /// # peripherals::periph!{
+    /// #     Timer;
+    /// #     rw CONTROL @ 0x00: u32 = 0x0000 {}
+    /// # }
+    /// use peripherals::mmap::Mapping;
+    ///
+    /// let mapping = Mapping::dev_mem(0x4000_0000, 0x2000)?;
+    /// let uart: &mut DynUart = unsafe { mapping.steal_block(0x0000)? };
+    /// let timer: &mut DynTimer = unsafe { mapping.steal_block(0x1000)? };
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The registers of the block must not be accessed through other references while the returned
+    /// one is in use, e.g. from another call to this method with an overlapping register block.
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn steal_block<B: DynBlock>(&self, offset: usize) -> io::Result<&mut B> {
+        match offset.checked_add(B::SIZE) {
+            Some(end) if end <= self.len() => {}
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "register block outside of the mapping",
+                ))
+            }
+        }
+        // The alignment of a register block is a power of two
+        let address = self.base() + offset;
+        if address & (B::ALIGN - 1) != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "misaligned register block",
+            ));
+        }
+        Ok(block_from_base(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{Data, DynPeripheral, Mode};
+
+    #[test]
+    fn mmap() {
+        use std::process;
+
+        let path = std::env::temp_dir().join(std::format!("peripherals-{}", process::id()));
+        fs::write(&path, [0u8; 0x20]).unwrap();
+        let mut mapping = Mapping::open(&path, 0x10, 0x10).unwrap();
+        assert_eq!(mapping.len(), 0x10);
+        assert!(mapping.block::<DynPeripheral>(0x0b).is_err());
+        assert!(mapping.block::<DynPeripheral>(0x01).is_err());
+
+        let periph = mapping.block::<DynPeripheral>(0x08).unwrap();
+        periph.config.modify(Mode::C);
+        periph.buffer.write(Value::reset() | Data(0x42));
+        assert_eq!(periph.config.read().value(), 0x0002);
+
+        let first = unsafe { mapping.steal_block::<DynPeripheral>(0x00) }.unwrap();
+        let second = unsafe { mapping.steal_block::<DynPeripheral>(0x08) }.unwrap();
+        first.config.modify(Mode::B);
+        second.buffer.write(Value::reset() | Data(0x24));
+        assert!(unsafe { mapping.steal_block::<DynPeripheral>(0x0b) }.is_err());
+        assert!(unsafe { mapping.steal_block::<DynPeripheral>(0x01) }.is_err());
+        core::mem::drop(mapping);
+
+        let content = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(content[0x10..0x12], [0x01, 0x00]);
+        assert_eq!(content[0x18..0x1e], [0x02, 0x00, 0x00, 0x00, 0x24, 0x00]);
+    }
+}
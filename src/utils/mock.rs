@@ -0,0 +1,293 @@
+//! A mock backend to test register accesses on the host
+//!
+//! [`Mock<P>`] is a peripheral instance with the same base address and name as `P`, but whose
+//! registers are stored in memory instead of being accessed with volatile reads and writes. This
+//! allows to test code that is generic over the peripheral instance on the host. All accesses are
+//! recorded and can be checked with [`take_log`].
+//!
+//! The memory and the log are thread-local, so that tests running in parallel don't interfere.
+//! Memory that wasn't written reads as zero. Note that `Dyn` peripherals and registers always
+//! access memory directly and can't be used with the mock backend.
+//!
+//! This module is only available with the `mock` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use peripherals::mock::{self, Access, Mock};
+//! use peripherals::{periph, device, Value};
+//!
+//! periph! {
+//!     MyPeripheral;
+//!     rw CONFIG @ 0x02: u16 = 0x0000 {
+//!         FLAG: 0 = struct Flag(bool);
+//!     }
+//! }
+//!
+//! device! {
+//!     MyMicrocontroller;
+//!     PERIPH @ 0x4000_0000: MyPeripheral;
+//! }
+//!
+//! mock::set::<CONFIG, PERIPH>(0x1234);
+//!
+//! let mut periph = unsafe { MyPeripheral::<Mock<PERIPH>>::steal() };
+//! periph.config.modify(Flag(true));
+//!
+//! assert_eq!(mock::get::<CONFIG, PERIPH>().value(), 0x1235);
+//! assert_eq!(mock::take_log(), [
+//!     Access::Read { address: 0x4000_0002, width: 2, value: 0x1234 },
+//!     Access::Write { address: 0x4000_0002, width: 2, value: 0x1235 },
+//! ]);
+//! ```
+
+use super::*;
+use core::cell::RefCell;
+use std::boxed::Box;
+use std::collections::BTreeMap;
+use std::thread_local;
+use std::vec::Vec;
+
+/// A mock peripheral instance
+///
+/// This has the same base address and name as `P`, but accesses are made in memory and recorded.
+/// See the [module documentation](self) for more details.
+pub struct Mock<P> {
+    _periph: PhantomData<P>,
+}
+
+impl<P: Peripheral> Peripheral for Mock<P> {
+    const BASE: usize = P::BASE;
+    const NAME: &'static str = P::NAME;
+
+    #[inline]
+    unsafe fn read<I: Int>(address: usize) -> I {
+        let hook = STATE.with(|state| state.borrow_mut().hooks.remove(&address));
+        if let Some(mut hook) = hook {
+            store(address, I::WIDTH / 8, hook(load(address, I::WIDTH / 8)));
+            STATE.with(|state| state.borrow_mut().hooks.insert(address, hook));
+        }
+
+        let value = load(address, I::WIDTH / 8);
+        STATE.with(|state| {
+            state.borrow_mut().log.push(Access::Read {
+                address,
+                width: I::WIDTH / 8,
+                value,
+            })
+        });
+        I::from_u128(value)
+    }
+
+    #[inline]
+    unsafe fn write<I: Int>(address: usize, value: I) {
+        store(address, I::WIDTH / 8, value.to_u128());
+        STATE.with(|state| {
+            state.borrow_mut().log.push(Access::Write {
+                address,
+                width: I::WIDTH / 8,
+                value: value.to_u128(),
+            })
+        });
+    }
+}
+
+impl<P: AtomicAlias> AtomicAlias for Mock<P> {
+    const SET: usize = P::SET;
+    const CLEAR: usize = P::CLEAR;
+    const TOGGLE: usize = P::TOGGLE;
+}
+
+impl<P: BitBand> BitBand for Mock<P> {}
+
+impl<P: WakerRegistry> WakerRegistry for Mock<P> {
+    #[inline]
+    fn register(waker: &core::task::Waker) {
+        P::register(waker);
+    }
+}
+
+/// An access made through the mock backend
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// A read of `width` bytes at `address`, which returned `value`
+    Read {
+        /// The address of the access
+        address: usize,
+        /// The width of the access, in bytes
+        width: usize,
+        /// The value that was read
+        value: u128,
+    },
+    /// A write of `value` on `width` bytes at `address`
+    Write {
+        /// The address of the access
+        address: usize,
+        /// The width of the access, in bytes
+        width: usize,
+        /// The value that was written
+        value: u128,
+    },
+}
+
+type Hook = Box<dyn FnMut(u128) -> u128>;
+
+#[derive(Default)]
+struct State {
+    memory: BTreeMap<usize, u8>,
+    log: Vec<Access>,
+    hooks: BTreeMap<usize, Hook>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+fn load(address: usize, width: usize) -> u128 {
+    STATE.with(|state| {
+        let state = state.borrow();
+        (0..width).fold(0, |value, i| {
+            let byte = state.memory.get(&(address + i)).copied().unwrap_or(0);
+            value | (byte as u128) << (i * 8)
+        })
+    })
+}
+
+fn store(address: usize, width: usize, value: u128) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        for i in 0..width {
+            state.memory.insert(address + i, (value >> (i * 8)) as u8);
+        }
+    });
+}
+
+/// Set the value of a register in memory, without recording an access
+///
+/// This allows to set the value that the code under test will read. The value is byte-swapped
+/// if the register is not in the native byte order.
+#[inline]
+pub fn set<R: Register, P: Peripheral>(value: R::Int) {
+    let value = swap_bytes::<R>(value);
+    store(P::BASE + R::OFFSET, R::Int::WIDTH / 8, value.to_u128());
+}
+
+/// Get the value of a register in memory, without recording an access
+///
+/// This allows to check the value written by the code under test. The value is byte-swapped if
+/// the register is not in the native byte order.
+#[inline]
+pub fn get<R: Register, P: Peripheral>() -> Value<R::Value> {
+    let value = R::Int::from_u128(load(P::BASE + R::OFFSET, R::Int::WIDTH / 8));
+    unsafe { Value::from_raw(swap_bytes::<R>(value)) }
+}
+
+/// Simulate hardware by calling a closure each time a register is read
+///
+/// The closure receives the value of the register in memory and returns the value to be read,
+/// which is also stored in memory. This allows e.g. to set a status flag after some time.
+///
+/// ```
+/// use peripherals::mock::{self, Mock};
+/// use peripherals::{periph, device, Reg};
+///
+/// periph! {
+///     MyPeripheral;
+///     r STATUS @ 0x00: u8 = 0x00 {
+///         READY: 0 = struct Ready(bool);
+///     }
+/// }
+///
+/// device! {
+///     MyMicrocontroller;
+///     PERIPH @ 0x4000_0000: MyPeripheral;
+/// }
+///
+/// let mut reads = 0;
+/// mock::on_read::<STATUS, PERIPH, _>(move |value| {
+///     reads += 1;
+///     value | Ready(reads >= 3)
+/// });
+///
+/// let status = unsafe { Reg::<STATUS, Mock<PERIPH>>::steal() };
+/// assert!(!status.test(Ready(true)));
+/// assert!(!status.test(Ready(true)));
+/// assert!(status.test(Ready(true)));
+/// ```
+#[inline]
+pub fn on_read<R, P, F>(mut hook: F)
+where
+    R: Register,
+    P: Peripheral,
+    F: FnMut(Value<R::Value>) -> Value<R::Value> + 'static,
+{
+    let hook = move |value| unsafe {
+        let value = hook(Value::from_raw(swap_bytes::<R>(R::Int::from_u128(value))));
+        swap_bytes::<R>(value.value()).to_u128()
+    };
+    STATE.with(|state| {
+        state
+            .borrow_mut()
+            .hooks
+            .insert(P::BASE + R::OFFSET, Box::new(hook))
+    });
+}
+
+/// Take the accesses recorded since the last call to this function
+#[inline]
+pub fn take_log() -> Vec<Access> {
+    STATE.with(|state| core::mem::take(&mut state.borrow_mut().log))
+}
+
+/// Clear the memory, the recorded accesses and the read hooks
+#[inline]
+pub fn reset() {
+    STATE.with(|state| *state.borrow_mut() = State::default());
+}
+
+/// The peripheral instance used by the tests of this crate
+#[cfg(test)]
+#[allow(clippy::upper_case_acronyms)]
+pub(crate) enum PERIPH {}
+
+#[cfg(test)]
+impl Peripheral for PERIPH {
+    const BASE: usize = 0x4001_0000;
+    const NAME: &'static str = "PERIPH";
+}
+
+#[cfg(test)]
+impl AtomicAlias for PERIPH {
+    const SET: usize = 0x2000;
+    const CLEAR: usize = 0x3000;
+    const TOGGLE: usize = 0x1000;
+}
+
+#[cfg(test)]
+impl BitBand for PERIPH {}
+
+/// A waker counting how many times it was woken
+#[cfg(test)]
+pub(crate) struct Counter(pub(crate) core::sync::atomic::AtomicUsize);
+
+#[cfg(test)]
+impl std::task::Wake for Counter {
+    fn wake(self: std::sync::Arc<Self>) {
+        self.0.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Poll a future with a counting waker, returning the number of wakes
+#[cfg(test)]
+pub(crate) fn poll<F: core::future::Future>(
+    future: core::pin::Pin<&mut F>,
+) -> (core::task::Poll<F::Output>, usize) {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::{Context, Waker};
+    use std::sync::Arc;
+
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let poll = future.poll(&mut Context::from_waker(&waker));
+    (poll, counter.0.load(Ordering::SeqCst))
+}
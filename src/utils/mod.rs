@@ -5,19 +5,54 @@ use core::fmt::{self, Debug};
 use core::marker::PhantomData;
 use core::ops::*;
 
+pub use access::*;
+pub use barrier::*;
+pub use block::*;
+pub use bus::*;
 pub use dynreg::*;
 pub use field::*;
 pub use field_values::*;
 pub use fields::*;
+pub use indirect::*;
 pub use reg::*;
+pub use region::*;
+pub use sequence::*;
+pub use shadowed::*;
+pub use snapshot::*;
+pub use stored::*;
+pub use taken::*;
+pub use timeout::*;
 pub use value::*;
+pub use verify::*;
+pub use waker::*;
 
+mod access;
+mod barrier;
+mod block;
+mod bus;
 mod dynreg;
 mod field;
 mod field_values;
 mod fields;
+mod fifo;
+mod indirect;
+#[cfg(all(feature = "std", unix))]
+pub mod mmap;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 mod reg;
+mod region;
+mod sequence;
+#[cfg(feature = "serde")]
+mod serialize;
+mod shadowed;
+mod snapshot;
+mod stored;
+mod taken;
+mod timeout;
 mod value;
+mod verify;
+mod waker;
 
 use private::*;
 mod private {
@@ -35,24 +70,67 @@ mod private {
         + fmt::Binary
         + fmt::LowerHex
         + Copy
+        + Sync
     {
         const WIDTH: usize;
-    }
 
-    impl Int for u8 {
-        const WIDTH: usize = 8;
-    }
-    impl Int for u16 {
-        const WIDTH: usize = 16;
+        type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default + Copy;
+
+        fn to_u128(self) -> u128;
+        fn from_u128(value: u128) -> Self;
+        fn swap_bytes(self) -> Self;
+        fn to_le_bytes(self) -> Self::Bytes;
+        fn from_le_bytes(bytes: Self::Bytes) -> Self;
     }
-    impl Int for u32 {
-        const WIDTH: usize = 32;
+
+    macro_rules! impl_int {
+        ($($int:ty: $width:literal),*) => {$(
+            impl Int for $int {
+                const WIDTH: usize = $width;
+
+                type Bytes = [u8; $width / 8];
+
+                #[inline]
+                fn to_u128(self) -> u128 {
+                    self as u128
+                }
+
+                #[inline]
+                fn from_u128(value: u128) -> $int {
+                    value as $int
+                }
+
+                #[inline]
+                fn swap_bytes(self) -> $int {
+                    <$int>::swap_bytes(self)
+                }
+
+                #[inline]
+                fn to_le_bytes(self) -> [u8; $width / 8] {
+                    <$int>::to_le_bytes(self)
+                }
+
+                #[inline]
+                fn from_le_bytes(bytes: [u8; $width / 8]) -> $int {
+                    <$int>::from_le_bytes(bytes)
+                }
+            }
+        )*};
     }
-    impl Int for u64 {
-        const WIDTH: usize = 64;
+
+    impl_int!(u8: 8, u16: 16, u32: 32, u64: 64, u128: 128);
+
+    /// Register accesses of a peripheral instance, without its type
+    ///
+    /// Offsets and widths are in bytes.
+    pub trait Backend {
+        fn read(&self, offset: usize, width: usize) -> u128;
+        fn write(&self, offset: usize, width: usize, value: u128);
     }
-    impl Int for u128 {
-        const WIDTH: usize = 128;
+
+    /// Apply a step of an init sequence
+    pub trait Apply {
+        fn apply(&self, backend: &dyn Backend) -> Result<(), TimedOut>;
     }
 
     pub trait Both<T> {
@@ -88,6 +166,72 @@ pub trait Peripheral {
     const BASE: usize;
     /// The name to be displayed in debug
     const NAME: &'static str;
+
+    /// Read a value at the given address
+    ///
+    /// This is a volatile read, except for the mock backend.
+    ///
+    /// # Safety
+    ///
+    /// The address must be valid for a volatile read.
+    #[doc(hidden)]
+    #[inline]
+    unsafe fn read<I: Int>(address: usize) -> I {
+        (address as *const I).read_volatile()
+    }
+
+    /// Write a value at the given address
+    ///
+    /// This is a volatile write, except for the mock backend.
+    ///
+    /// # Safety
+    ///
+    /// The address must be valid for a volatile write.
+    #[doc(hidden)]
+    #[inline]
+    unsafe fn write<I: Int>(address: usize, value: I) {
+        (address as *mut I).write_volatile(value)
+    }
+}
+
+/// A trait for peripheral instances with atomic set, clear and toggle aliases
+///
+/// This trait is implemented by the [`device!`] macro for peripheral instances declared with alias
+/// offsets. Each alias is at the given offset from the address of the register. Writing a mask to
+/// an alias sets, clears or toggles the corresponding bits of the register.
+pub trait AtomicAlias: Peripheral {
+    /// Offset of the alias that sets bits
+    const SET: usize;
+    /// Offset of the alias that clears bits
+    const CLEAR: usize;
+    /// Offset of the alias that toggles bits
+    const TOGGLE: usize;
+}
+
+/// A marker trait for peripheral instances in a bit-band region
+///
+/// This trait is implemented by the [`device!`] macro for peripheral instances declared with the
+/// `bit_band` option. It allows to atomically modify single-bit fields with
+/// [`Reg::modify_bit_band`].
+pub trait BitBand: Peripheral {}
+
+/// Compute the address of a bit in the bit-band alias region
+///
+/// On Cortex-M3 and Cortex-M4, each bit of the first MiB of the SRAM (at `0x2000_0000`) and
+/// peripheral (at `0x4000_0000`) regions is mapped to a word in the corresponding alias region (at
+/// `0x2200_0000` and `0x4200_0000`). Writing 0 or 1 to this word clears or sets the bit.
+///
+/// ```
+/// use peripherals::bit_band_address;
+///
+/// assert_eq!(bit_band_address(0x4000_0000, 0), 0x4200_0000);
+/// assert_eq!(bit_band_address(0x4001_0004, 3), 0x4220_008c);
+/// assert_eq!(bit_band_address(0x2000_0100, 31), 0x2200_207c);
+/// ```
+#[inline]
+pub const fn bit_band_address(address: usize, bit: usize) -> usize {
+    let address = address as u32;
+    ((address & 0xf000_0000) + 0x0200_0000 + (address & 0x000f_ffff) * 32 + bit as u32 * 4) as usize
 }
 
 /// A trait for the register associated with a value
@@ -100,8 +244,25 @@ pub trait RegisterValue {
     const RESET: Self::Int;
     /// The name to be displayed in debug
     const NAME: &'static str;
+    /// The fields of this register, in declaration order
+    const FIELDS: &'static [FieldInfo] = &[];
+
+    /// Check that the value of each field can be converted to its type
+    ///
+    /// This is used to validate values built from raw integers or bytes, e.g. with
+    /// [`Value::try_from_raw`].
+    #[inline]
+    fn is_valid(_value: Self::Int) -> bool {
+        true
+    }
 }
 
+/// The bytes of a register value, e.g. `[u8; 2]` for a 16-bit register
+///
+/// This is the array taken and returned by [`Value::to_le_bytes`], [`Value::from_le_bytes`] and
+/// their big-endian variants.
+pub type Bytes<R> = <<R as RegisterValue>::Int as Int>::Bytes;
+
 /// A trait for registers
 //
 /// This trait is implemented by the [`periph!`] macro for marker types that indicate registers.
@@ -115,6 +276,53 @@ pub trait Register {
     const OFFSET: usize;
     /// The name to be displayed in debug
     const NAME: &'static str;
+    /// The byte order of this register in memory
+    ///
+    /// Values are byte-swapped when reading and writing registers that are not in the native byte
+    /// order, so that [`Value`] always holds the logical value of the register.
+    const ENDIANNESS: Endianness = Endianness::NATIVE;
+    /// The width of the accesses to this register, in bytes
+    ///
+    /// This is usually the width of the register. If it is wider, the register is in the least
+    /// significant bits of each access. If it is narrower, each access is split in several
+    /// accesses at consecutive addresses, in [`Register::SPLIT_ORDER`].
+    const ACCESS_WIDTH: usize = core::mem::size_of::<Self::Int>();
+    /// The order of the accesses when they are narrower than the register
+    const SPLIT_ORDER: SplitOrder = SplitOrder::LowFirst;
+}
+
+/// A marker trait for the registers of a peripheral
+///
+/// This trait is implemented by the [`periph!`] macro for each register, with the `Dyn` struct of
+/// the peripheral as `B`. It restricts [`InitSequence`]s to the registers of their peripheral.
+pub trait RegisterOf<B>: Register {}
+
+/// The byte order of registers in memory, or of addresses and values on a [`Bus`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+impl Endianness {
+    /// The byte order of the target
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endianness = Endianness::Little;
+    /// The byte order of the target
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endianness = Endianness::Big;
+}
+
+/// Convert between the value of a register and its representation in memory
+#[inline]
+fn swap_bytes<R: Register>(value: R::Int) -> R::Int {
+    if R::ENDIANNESS == Endianness::NATIVE {
+        value
+    } else {
+        value.swap_bytes()
+    }
 }
 
 /// A marker trait for readable registers
@@ -123,6 +331,14 @@ pub trait ReadRegister: Register {}
 /// A marker trait for writeable register
 pub trait WriteRegister: Register {}
 
+/// A marker trait for FIFO or data port registers
+///
+/// These registers are read or written repeatedly at the same address, e.g. to transfer data to
+/// or from a serial peripheral. This trait is implemented by the [`periph!`] macro for registers
+/// declared with `fifo`, and allows to use [`Reg::write_iter`], [`Reg::read_into`] and similar
+/// methods.
+pub trait FifoRegister: Register {}
+
 /// A marker type for toggleable fields
 #[derive(Debug)]
 pub enum Toggle {}
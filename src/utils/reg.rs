@@ -11,9 +11,26 @@ pub struct Reg<R, P> {
 }
 
 impl<R: Register, P: Peripheral> Reg<R, P> {
+    /// Create a register handle
+    ///
+    /// Registers are normally obtained from the peripheral struct generated by the [`periph!`]
+    /// macro, which can be destructured to give each register to a different driver.
+    ///
+    /// # Safety
+    ///
+    /// There must be only one handle to each register of each peripheral instance in the whole
+    /// program. Creating more handles allows to modify the same register from different places.
+    #[inline]
+    pub const unsafe fn steal() -> Reg<R, P> {
+        Reg {
+            _periph: PhantomData,
+            _reg: PhantomData,
+        }
+    }
+
     /// Get a raw pointer to the register
     #[inline]
-    pub fn ptr(&self) -> *const R::Int {
+    pub const fn ptr(&self) -> *const R::Int {
         (P::BASE + R::OFFSET) as *const R::Int
     }
 
@@ -38,7 +55,7 @@ impl<R: ReadRegister, P: Peripheral> Reg<R, P> {
     /// This returns a [`Value`], which can be used to read fields or modified and written back.
     #[inline]
     pub fn read(&self) -> Value<R::Value> {
-        unsafe { Value::from_raw(self.ptr().read_volatile()) }
+        unsafe { Value::from_raw(read_register::<R, _>(&Instance::<P>::new(), R::OFFSET)) }
     }
 
     /// Read the given field
@@ -71,6 +88,51 @@ impl<R: ReadRegister, P: Peripheral> Reg<R, P> {
     pub fn test<B: Into<FieldValues<R::Value>>>(&self, bits: B) -> bool {
         self.read().test(bits)
     }
+
+    /// Wait until the given fields have the given values
+    ///
+    /// This reads the register until [`Value::test`] returns `true`, and returns the last value
+    /// read. The [`Timeout`] is checked after each read, so that this doesn't hang forever if the
+    /// hardware misbehaves.
+    ///
+    /// See [`DynReg::wait_until`] for an example.
+    #[inline]
+    pub fn wait_until<B: Into<FieldValues<R::Value>>, T: Timeout>(
+        &self,
+        bits: B,
+        mut timeout: T,
+    ) -> Result<Value<R::Value>, TimedOut> {
+        let bits = bits.into();
+        loop {
+            let value = self.read();
+            if value.test(bits) {
+                return Ok(value);
+            } else if timeout.expired() {
+                return Err(TimedOut);
+            }
+        }
+    }
+
+    /// Wait while the given fields have the given values
+    ///
+    /// This is the opposite of [`Reg::wait_until`]: it reads the register until [`Value::test`]
+    /// returns `false`.
+    #[inline]
+    pub fn wait_while<B: Into<FieldValues<R::Value>>, T: Timeout>(
+        &self,
+        bits: B,
+        mut timeout: T,
+    ) -> Result<Value<R::Value>, TimedOut> {
+        let bits = bits.into();
+        loop {
+            let value = self.read();
+            if !value.test(bits) {
+                return Ok(value);
+            } else if timeout.expired() {
+                return Err(TimedOut);
+            }
+        }
+    }
 }
 
 impl<R: WriteRegister, P: Peripheral> Reg<R, P> {
@@ -79,9 +141,7 @@ impl<R: WriteRegister, P: Peripheral> Reg<R, P> {
     /// This takes a [`Value`], which can be read from a register or created with `Default`.
     #[inline]
     pub fn write(&mut self, value: Value<R::Value>) {
-        unsafe {
-            self.ptr_mut().write_volatile(value.value());
-        }
+        write_register::<R, _>(&Instance::<P>::new(), R::OFFSET, value.value());
     }
 
     /// Reset this register
@@ -91,6 +151,15 @@ impl<R: WriteRegister, P: Peripheral> Reg<R, P> {
     pub fn reset(&mut self) {
         self.write(Value::reset());
     }
+
+    /// Write a value computed from the reset value
+    ///
+    /// The closure receives the reset value of this register and returns the value to write. See
+    /// [`Reg::modify_with`] for an example.
+    #[inline]
+    pub fn write_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(Value::reset()));
+    }
 }
 
 impl<R: ReadRegister + WriteRegister, P: Peripheral> Reg<R, P> {
@@ -103,6 +172,17 @@ impl<R: ReadRegister + WriteRegister, P: Peripheral> Reg<R, P> {
         self.write(self.read() | bits);
     }
 
+    /// Modify this register with a closure
+    ///
+    /// The closure receives the current value of this register and returns the value to write.
+    /// This allows to modify the register depending on its current value.
+    ///
+    /// See [`DynReg::modify_with`] for an example.
+    #[inline]
+    pub fn modify_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(self.read()));
+    }
+
     /// Toggle the given fields
     ///
     /// This takes a toggleable field defined for this register by the [`periph!`] macro. These
@@ -114,8 +194,453 @@ impl<R: ReadRegister + WriteRegister, P: Peripheral> Reg<R, P> {
     }
 }
 
+impl<R: ReadRegister + WriteRegister, P: BitBand> Reg<R, P> {
+    /// Atomically modify single-bit fields through the bit-band alias region
+    ///
+    /// Each bit is written with a single word write to its address in the bit-band alias region
+    /// (see [`bit_band_address`]), without a read-modify-write. It thus only needs a shared
+    /// reference. This takes the value of a toggleable field defined for this register by the
+    /// [`periph!`] macro. These values can also be combined with the `|` operator, in which case
+    /// each field is written separately.
+    ///
+    /// ```no_run
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     rw CONFIG @ 0x04: u32 = 0x0000 {
+    /// #         FLAG: 3 = struct Flag(bool);
+    /// #     }
+    /// # }
+    /// peripherals::device!{
+    ///     MyMicrocontroller;
+    ///     PERIPH @ 0x4001_0000: MyPeripheral { bit_band };
+    /// }
+    ///
+    /// let periph = MyMicrocontroller::take().unwrap().periph;
+    /// periph.config.modify_bit_band(Flag(true)); // Writes 1 to 0x4220_008c
+    /// ```
+    #[inline]
+    pub fn modify_bit_band<B: Into<FieldValues<R::Value, Toggle>>>(&self, bits: B) {
+        let bits = bits.into();
+        let (mask, bits) = (swap_bytes::<R>(bits.mask()), swap_bytes::<R>(bits.bits()));
+        let zero = R::Int::default();
+        let one = !(!zero << 1);
+        for bit in 0..<R::Int as Int>::WIDTH {
+            if (mask >> bit) & one != zero {
+                let address = bit_band_address(P::BASE + R::OFFSET, bit);
+                let value = (bits >> bit) & one != zero;
+                unsafe {
+                    P::write(address, value as u32);
+                }
+            }
+        }
+    }
+}
+
+impl<R: WriteRegister, P: AtomicAlias> Reg<R, P> {
+    /// Atomically set the given fields
+    ///
+    /// This writes the mask of the fields to the set alias of the register, without a
+    /// read-modify-write. It thus only needs a shared reference. This takes a toggleable field
+    /// defined for this register by the [`periph!`] macro. These fields can also be combined with
+    /// the `|` operator.
+    ///
+    /// ```no_run
+    /// # peripherals::periph!{
+    /// #     Gpio;
+    /// #     rw OUT @ 0x00: u32 = 0x0000 {
+    /// #         PIN0: 0 = struct Pin0(bool);
+    /// #         PIN1: 1 = struct Pin1(bool);
+    /// #     }
+    /// # }
+    /// peripherals::device!{
+    ///     MyMicrocontroller;
+    ///     GPIO @ 0x4001_4000: Gpio { set: 0x2000, clear: 0x3000, toggle: 0x1000 };
+    /// }
+    ///
+    /// let gpio = MyMicrocontroller::take().unwrap().gpio;
+    /// gpio.out.set_bits(OUT::PIN0 | OUT::PIN1); // Writes 0b11 to 0x4001_6000
+    /// gpio.out.clear_bits(OUT::PIN1); // Writes 0b10 to 0x4001_7000
+    /// gpio.out.toggle_atomic(OUT::PIN0); // Writes 0b01 to 0x4001_5000
+    /// ```
+    #[inline]
+    pub fn set_bits<F: Into<Fields<R::Value, Toggle>>>(&self, fields: F) {
+        let offset = P::SET + R::OFFSET;
+        write_register::<R, _>(&Instance::<P>::new(), offset, fields.into().mask());
+    }
+
+    /// Atomically clear the given fields
+    ///
+    /// This writes the mask of the fields to the clear alias of the register. See
+    /// [`Reg::set_bits`] for more details.
+    #[inline]
+    pub fn clear_bits<F: Into<Fields<R::Value, Toggle>>>(&self, fields: F) {
+        let offset = P::CLEAR + R::OFFSET;
+        write_register::<R, _>(&Instance::<P>::new(), offset, fields.into().mask());
+    }
+
+    /// Atomically toggle the given fields
+    ///
+    /// This writes the mask of the fields to the toggle alias of the register. See
+    /// [`Reg::set_bits`] for more details.
+    #[inline]
+    pub fn toggle_atomic<F: Into<Fields<R::Value, Toggle>>>(&self, fields: F) {
+        let offset = P::TOGGLE + R::OFFSET;
+        write_register::<R, _>(&Instance::<P>::new(), offset, fields.into().mask());
+    }
+}
+
+impl<R: ReadRegister, P: WakerRegistry> Reg<R, P> {
+    /// Asynchronously wait until the given fields have the given values
+    ///
+    /// The returned future reads the register each time it is polled, and completes with the
+    /// value read once [`Value::test`] returns `true`. Between polls, the task is woken as
+    /// described by the [`WakerRegistry`] implementation of the peripheral instance: either
+    /// immediately, or by the interrupt handler of the peripheral.
+    ///
+    /// ```no_run
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     r STATUS @ 0x00: u32 = 0x0000 {
+    /// #         READY: 0 = struct Ready(bool);
+    /// #     }
+    /// # }
+    /// peripherals::device!{
+    ///     MyMicrocontroller;
+    ///     PERIPH @ 0x4001_0000: MyPeripheral { waker };
+    /// }
+    ///
+    /// // In the interrupt handler of the peripheral
+    /// fn interrupt() {
+    ///     PERIPH::waker().wake();
+    /// }
+    ///
+    /// async fn driver(periph: MyPeripheral<PERIPH>) {
+    ///     periph.status.wait_for(Ready(true)).await;
+    /// }
+    /// ```
+    #[inline]
+    pub fn wait_for<B: Into<FieldValues<R::Value>>>(&self, bits: B) -> WaitFor<'_, R, P> {
+        WaitFor::new(self, bits.into())
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<R: WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Write a value to this register from a shared reference
+    ///
+    /// Same as [`Reg::write`], but inside a critical section.
+    #[inline]
+    pub fn write_cs(&self, value: Value<R::Value>) {
+        critical_section::with(|_| {
+            write_register::<R, _>(&Instance::<P>::new(), R::OFFSET, value.value());
+        });
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<R: ReadRegister + WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Modify the given fields from a shared reference
+    ///
+    /// Same as [`Reg::modify`], but the read-modify-write is done inside a critical section. This
+    /// allows to share a register between the main loop and interrupt handlers.
+    ///
+    /// ```no_run
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     rw CONFIG @ 0x00: u16 = 0x0000 {
+    /// #         FLAG: 0 = struct Flag(bool);
+    /// #     }
+    /// # }
+    /// # peripherals::device!{
+    /// #     MyMicrocontroller;
+    /// #     PERIPH @ 0x1234: MyPeripheral;
+    /// # }
+    /// use peripherals::Reg;
+    ///
+    /// // The register can be shared with interrupt handlers
+    /// static SHARED: Reg<CONFIG, PERIPH> = unsafe { Reg::steal() };
+    ///
+    /// SHARED.modify_cs(Flag(true));
+    /// ```
+    #[inline]
+    pub fn modify_cs<B: Into<FieldValues<R::Value>>>(&self, bits: B) {
+        critical_section::with(|_| {
+            let value = self.read() | bits;
+            write_register::<R, _>(&Instance::<P>::new(), R::OFFSET, value.value());
+        });
+    }
+
+    /// Toggle the given fields from a shared reference
+    ///
+    /// Same as [`Reg::toggle`], but the read-modify-write is done inside a critical section.
+    #[inline]
+    pub fn toggle_cs<F: Into<Fields<R::Value, Toggle>>>(&self, fields: F) {
+        let fields = fields.into();
+        critical_section::with(|_| {
+            let value = self.read() ^ fields;
+            write_register::<R, _>(&Instance::<P>::new(), R::OFFSET, value.value());
+        });
+    }
+}
+
 impl<R: Register, P: Peripheral> Debug for Reg<R, P> {
+    #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(fmt, "Reg {}.{} @ 0x{:06p}", P::NAME, R::NAME, self.ptr())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{self, CfgFlag, Mode, StatFlag, BUFFER, CONFIG, STATUS};
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+
+    #[test]
+    fn steal() {
+        let periph::Peripheral::<PERIPH> {
+            config,
+            status,
+            buffer,
+        } = unsafe { periph::Peripheral::steal() };
+        assert_eq!(
+            config.ptr(),
+            unsafe { Reg::<CONFIG, PERIPH>::steal() }.ptr()
+        );
+        assert_eq!(
+            status.ptr(),
+            unsafe { Reg::<STATUS, PERIPH>::steal() }.ptr()
+        );
+        assert_eq!(
+            buffer.ptr(),
+            unsafe { Reg::<BUFFER, PERIPH>::steal() }.ptr()
+        );
+    }
+
+    #[cfg(feature = "critical-section")]
+    #[test]
+    fn critical_section() {
+        let config = unsafe { Reg::<CONFIG, Mock<PERIPH>>::steal() };
+        config.write_cs(Value::reset() | Mode::B);
+        config.modify_cs(CfgFlag::True);
+        config.toggle_cs(CONFIG::FLAG);
+        assert_eq!(mock::get::<CONFIG, PERIPH>().value(), 0x0001);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0005,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0005,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+            ]
+        );
+    }
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod aliased {
+        crate::device! {
+            AliasedDevice;
+            ALIASED @ 0x4003_0000: crate::example::periph::Peripheral {
+                set: 0x100, clear: 0x200, toggle: 0x300
+            };
+        }
+    }
+
+    #[test]
+    fn atomic_alias_device() {
+        use aliased::ALIASED;
+
+        assert_eq!(<ALIASED as AtomicAlias>::SET, 0x100);
+        assert_eq!(<ALIASED as AtomicAlias>::CLEAR, 0x200);
+        assert_eq!(<ALIASED as AtomicAlias>::TOGGLE, 0x300);
+
+        let config = unsafe { Reg::<CONFIG, Mock<ALIASED>>::steal() };
+        mock::set::<CONFIG, ALIASED>(0x0003);
+        config.set_bits(CONFIG::FLAG);
+        config.clear_bits(CONFIG::FLAG);
+        config.toggle_atomic(CONFIG::FLAG);
+        // The register itself is neither read nor written
+        assert_eq!(mock::get::<CONFIG, ALIASED>().value(), 0x0003);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4003_0100,
+                    width: 2,
+                    value: 0x04,
+                },
+                Access::Write {
+                    address: 0x4003_0200,
+                    width: 2,
+                    value: 0x04,
+                },
+                Access::Write {
+                    address: 0x4003_0300,
+                    width: 2,
+                    value: 0x04,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn atomic_alias() {
+        let config = unsafe { Reg::<CONFIG, Mock<PERIPH>>::steal() };
+        config.set_bits(CONFIG::FLAG);
+        config.clear_bits(CONFIG::FLAG);
+        config.toggle_atomic(CONFIG::FLAG);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4001_2000,
+                    width: 2,
+                    value: 0x04,
+                },
+                Access::Write {
+                    address: 0x4001_3000,
+                    width: 2,
+                    value: 0x04,
+                },
+                Access::Write {
+                    address: 0x4001_1000,
+                    width: 2,
+                    value: 0x04,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn bit_band() {
+        let config = unsafe { Reg::<CONFIG, Mock<PERIPH>>::steal() };
+        config.modify_bit_band(CfgFlag::True);
+        config.modify_bit_band(CfgFlag::False);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4220_0008,
+                    width: 4,
+                    value: 1,
+                },
+                Access::Write {
+                    address: 0x4220_0008,
+                    width: 4,
+                    value: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn modify_with() {
+        let mut config = unsafe { Reg::<CONFIG, Mock<PERIPH>>::steal() };
+        mock::set::<CONFIG, PERIPH>(0x0004);
+        config.modify_with(|value| {
+            if value.test(CfgFlag::True) {
+                value | Mode::C
+            } else {
+                value
+            }
+        });
+        assert_eq!(mock::get::<CONFIG, PERIPH>().value(), 0x0006);
+        config.write_with(|value| value | Mode::B);
+        assert_eq!(mock::get::<CONFIG, PERIPH>().value(), 0x0001);
+    }
+
+    #[test]
+    fn wait() {
+        let status = unsafe { Reg::<STATUS, Mock<PERIPH>>::steal() };
+        let mut reads = 0;
+        mock::on_read::<STATUS, PERIPH, _>(move |value| {
+            reads += 1;
+            value | StatFlag(reads >= 3)
+        });
+
+        assert_eq!(status.wait_until(StatFlag(true), 2), Err(TimedOut));
+        assert_eq!(mock::take_log().len(), 2);
+        assert_eq!(
+            status.wait_until(StatFlag(true), 2).map(|v| v.value()),
+            Ok(0x0004)
+        );
+        assert_eq!(mock::take_log().len(), 1);
+        assert_eq!(status.wait_while(StatFlag(true), 3), Err(TimedOut));
+        assert_eq!(mock::take_log().len(), 3);
+        assert_eq!(
+            status
+                .wait_while(StatFlag(false), || false)
+                .map(|v| v.value()),
+            Ok(0x0004)
+        );
+    }
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod big_endian {
+        crate::periph! {
+            BigEndian big_endian;
+            rw DATA @ 0x00: u32 = 0x0000 {
+                LOW: 0 = struct Low(bool);
+                HIGH: 24..31 = struct High(u8);
+            }
+        }
+    }
+
+    #[test]
+    fn big_endian() {
+        use big_endian::{High, Low, DATA};
+
+        let mut data = unsafe { Reg::<DATA, Mock<PERIPH>>::steal() };
+        mock::set::<DATA, PERIPH>(0x1200_0000);
+        data.modify(Low(true));
+        assert_eq!(mock::get::<DATA, PERIPH>().field(DATA::HIGH), High(0x12));
+        data.set_bits(DATA::LOW);
+        data.modify_bit_band(Low(false));
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 4,
+                    value: 0x0000_0012,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 4,
+                    value: 0x0100_0012,
+                },
+                Access::Write {
+                    address: 0x4001_2000,
+                    width: 4,
+                    value: 0x0100_0000,
+                },
+                Access::Write {
+                    address: bit_band_address(0x4001_0000, 24),
+                    width: 4,
+                    value: 0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,450 @@
+use super::*;
+use core::mem::size_of;
+
+/// A trait for memory regions
+///
+/// This trait is implemented by the [`periph!`] macro for marker types that indicate memory
+/// regions, i.e. arrays of values embedded in a peripheral such as packet buffers or look-up
+/// tables.
+pub trait Region {
+    /// The width of the elements of this region (`u8`, `u16`, etc.)
+    type Int: Int;
+    /// The marker type for the elements of this region
+    type Value: RegisterValue<Int = Self::Int>;
+
+    /// The offset of the first element from the base address
+    const OFFSET: usize;
+    /// The number of elements
+    const LEN: usize;
+    /// The name to be displayed in debug
+    const NAME: &'static str;
+    /// The byte order of the elements in memory
+    const ENDIANNESS: Endianness = Endianness::NATIVE;
+}
+
+/// A marker trait for readable memory regions
+pub trait ReadRegion: Region {}
+
+/// A marker trait for writeable memory regions
+pub trait WriteRegion: Region {}
+
+/// Compute the offset, size and alignment of a memory region
+#[doc(hidden)]
+#[inline]
+pub const fn region_layout<M: Region>() -> (usize, usize, usize) {
+    (
+        M::OFFSET,
+        M::LEN * size_of::<M::Int>(),
+        core::mem::align_of::<M::Int>(),
+    )
+}
+
+/// The offset of an element, panicking if it is out of bounds
+#[inline]
+fn element<M: Region>(index: usize) -> usize {
+    if index >= M::LEN {
+        panic!(
+            "index out of bounds: {} has {} elements but the index is {}",
+            M::NAME,
+            M::LEN,
+            index
+        );
+    }
+    M::OFFSET + index * size_of::<M::Int>()
+}
+
+/// Check that a range of elements is in bounds
+#[inline]
+fn check_range<M: Region>(start: usize, len: usize) {
+    match start.checked_add(len) {
+        Some(end) if end <= M::LEN => {}
+        _ => panic!(
+            "range out of bounds: {} has {} elements but the range is {}..{}",
+            M::NAME,
+            M::LEN,
+            start,
+            start.saturating_add(len)
+        ),
+    }
+}
+
+#[inline]
+fn read_element<M: Region, B: Backend>(backend: &B, offset: usize) -> Value<M::Value> {
+    let value = M::Int::from_u128(backend.read(offset, size_of::<M::Int>()));
+    let value = if M::ENDIANNESS == Endianness::NATIVE {
+        value
+    } else {
+        value.swap_bytes()
+    };
+    unsafe { Value::from_raw(value) }
+}
+
+#[inline]
+fn write_element<M: Region, B: Backend>(backend: &B, offset: usize, value: Value<M::Value>) {
+    let value = if M::ENDIANNESS == Endianness::NATIVE {
+        value.value()
+    } else {
+        value.value().swap_bytes()
+    };
+    backend.write(offset, size_of::<M::Int>(), value.to_u128());
+}
+
+/// A memory region embedded in a peripheral
+///
+/// This is a volatile array of [`Value`]s, e.g. a packet buffer or a look-up table. It is created
+/// as part of the peripheral struct generated by the [`periph!`] macro for regions declared with a
+/// length. All accesses are bounds-checked, and each element is accessed with a single volatile
+/// access of its width.
+///
+/// ```no_run
+/// # peripherals::periph!{
+/// #     Can;
+/// #     rw RAM @ 0x100: u32[4] = 0x0000_0000 {
+/// #         ID: 0..10 = struct Id(u16);
+/// #         DLC: 16..19 = struct Dlc(u8);
+/// #     }
+/// # }
+/// # peripherals::device!{
+/// #     MyMicrocontroller;
+/// #     CAN @ 0x4000_6400: Can;
+/// # }
+/// use peripherals::Value;
+///
+/// let mut can = MyMicrocontroller::take().unwrap().can;
+///
+/// can.ram.write(0, Value::reset() | Id(0x123) | Dlc(8));
+/// let id = can.ram.read(0).field(RAM::ID);
+///
+/// let mut buffer = [Value::reset(); 2];
+/// can.ram.copy_to_slice(2, &mut buffer);
+/// assert!(can.ram.get(4).is_none());
+/// ```
+pub struct Memory<M, P> {
+    _periph: PhantomData<P>,
+    _region: PhantomData<M>,
+}
+
+impl<M: Region, P: Peripheral> Memory<M, P> {
+    /// Create a memory region handle
+    ///
+    /// # Safety
+    ///
+    /// There must be only one handle to each memory region of each peripheral instance in the
+    /// whole program. See [`Reg::steal`].
+    #[inline]
+    pub const unsafe fn steal() -> Memory<M, P> {
+        Memory {
+            _periph: PhantomData,
+            _region: PhantomData,
+        }
+    }
+
+    /// The number of elements of this region
+    #[inline]
+    pub const fn len(&self) -> usize {
+        M::LEN
+    }
+
+    /// Whether this region has no elements
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        M::LEN == 0
+    }
+
+    /// Get a raw pointer to the first element
+    #[inline]
+    pub const fn ptr(&self) -> *const M::Int {
+        (P::BASE + M::OFFSET) as *const M::Int
+    }
+
+    /// Get a mutable raw pointer to the first element
+    #[inline]
+    pub fn ptr_mut(&mut self) -> *mut M::Int {
+        (P::BASE + M::OFFSET) as *mut M::Int
+    }
+
+    /// Erase peripheral information
+    ///
+    /// This allows to choose at runtime which instance of a peripheral to use.
+    #[inline]
+    pub fn into_dyn(self) -> &'static mut DynMemory<M> {
+        unsafe { &mut *(P::BASE as *mut _) }
+    }
+}
+
+impl<M: ReadRegion, P: Peripheral> Memory<M, P> {
+    /// Read the element at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn read(&self, index: usize) -> Value<M::Value> {
+        read_element::<M, _>(&Instance::<P>::new(), element::<M>(index))
+    }
+
+    /// Read the element at the given index, or `None` if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<Value<M::Value>> {
+        if index < M::LEN {
+            Some(self.read(index))
+        } else {
+            None
+        }
+    }
+
+    /// Read consecutive elements into a buffer, starting at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are out of bounds.
+    #[inline]
+    pub fn copy_to_slice(&self, start: usize, buffer: &mut [Value<M::Value>]) {
+        check_range::<M>(start, buffer.len());
+        for (index, element) in (start..).zip(buffer) {
+            *element = self.read(index);
+        }
+    }
+}
+
+impl<M: WriteRegion, P: Peripheral> Memory<M, P> {
+    /// Write the element at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn write(&mut self, index: usize, value: Value<M::Value>) {
+        write_element::<M, _>(&Instance::<P>::new(), element::<M>(index), value);
+    }
+
+    /// Write consecutive elements from a buffer, starting at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements are out of bounds.
+    #[inline]
+    pub fn copy_from_slice(&mut self, start: usize, values: &[Value<M::Value>]) {
+        check_range::<M>(start, values.len());
+        for (index, &value) in (start..).zip(values) {
+            self.write(index, value);
+        }
+    }
+}
+
+impl<M: ReadRegion + WriteRegion, P: Peripheral> Memory<M, P> {
+    /// Modify the given fields of the element at the given index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn modify<B: Into<FieldValues<M::Value>>>(&mut self, index: usize, bits: B) {
+        let value = self.read(index) | bits;
+        self.write(index, value);
+    }
+}
+
+impl<M: Region, P: Peripheral> Debug for Memory<M, P> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Memory {}.{}[{}] @ 0x{:06p}",
+            P::NAME,
+            M::NAME,
+            M::LEN,
+            self.ptr()
+        )
+    }
+}
+
+/// A memory region from a peripheral only known at runtime
+///
+/// It has the same methods than the [`Memory`] type. It is created by [`Memory::into_dyn`] or as
+/// part of the struct created by `peripheral.into_dyn()`.
+pub struct DynMemory<M> {
+    _region: PhantomData<M>,
+}
+
+impl<M: Region> DynMemory<M> {
+    /// The number of elements of this region
+    #[inline]
+    pub const fn len(&self) -> usize {
+        M::LEN
+    }
+
+    /// Whether this region has no elements
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        M::LEN == 0
+    }
+
+    /// Raw pointer to the first element
+    #[inline]
+    pub fn ptr(&self) -> *const M::Int {
+        (self as *const _ as usize + M::OFFSET) as *const M::Int
+    }
+
+    /// Mutable raw pointer to the first element
+    #[inline]
+    pub fn ptr_mut(&mut self) -> *mut M::Int {
+        (self as *mut _ as usize + M::OFFSET) as *mut M::Int
+    }
+
+    /// Accesses from the base address of the peripheral
+    fn pointer(&self) -> Pointer {
+        unsafe { Pointer::new(self as *const _ as usize) }
+    }
+}
+
+impl<M: ReadRegion> DynMemory<M> {
+    /// Read the element at the given index
+    ///
+    /// Same as [`Memory::read`].
+    #[inline]
+    pub fn read(&self, index: usize) -> Value<M::Value> {
+        read_element::<M, _>(&self.pointer(), element::<M>(index))
+    }
+
+    /// Read the element at the given index, or `None` if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<Value<M::Value>> {
+        if index < M::LEN {
+            Some(self.read(index))
+        } else {
+            None
+        }
+    }
+
+    /// Read consecutive elements into a buffer, starting at the given index
+    ///
+    /// Same as [`Memory::copy_to_slice`].
+    #[inline]
+    pub fn copy_to_slice(&self, start: usize, buffer: &mut [Value<M::Value>]) {
+        check_range::<M>(start, buffer.len());
+        for (index, element) in (start..).zip(buffer) {
+            *element = self.read(index);
+        }
+    }
+}
+
+impl<M: WriteRegion> DynMemory<M> {
+    /// Write the element at the given index
+    ///
+    /// Same as [`Memory::write`].
+    #[inline]
+    pub fn write(&mut self, index: usize, value: Value<M::Value>) {
+        write_element::<M, _>(&self.pointer(), element::<M>(index), value);
+    }
+
+    /// Write consecutive elements from a buffer, starting at the given index
+    ///
+    /// Same as [`Memory::copy_from_slice`].
+    #[inline]
+    pub fn copy_from_slice(&mut self, start: usize, values: &[Value<M::Value>]) {
+        check_range::<M>(start, values.len());
+        for (index, &value) in (start..).zip(values) {
+            self.write(index, value);
+        }
+    }
+}
+
+impl<M: ReadRegion + WriteRegion> DynMemory<M> {
+    /// Modify the given fields of the element at the given index
+    ///
+    /// Same as [`Memory::modify`].
+    #[inline]
+    pub fn modify<B: Into<FieldValues<M::Value>>>(&mut self, index: usize, bits: B) {
+        let value = self.read(index) | bits;
+        self.write(index, value);
+    }
+}
+
+impl<M: Region> Debug for DynMemory<M> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "DynMemory {}[{}] @ 0x{:06p}",
+            M::NAME,
+            M::LEN,
+            self.ptr()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod region {
+        crate::periph! {
+            WithRegion big_endian;
+            rw CONTROL @ 0x00: u16 = 0x0000 {}
+            rw BUFFER @ 0x04: u16[4] = 0x0000 {
+                DATA: 0..7 = struct Data(u8);
+            }
+        }
+    }
+
+    #[test]
+    fn region() {
+        use region::{Data, DynWithRegion, WithRegion, BUFFER};
+
+        assert_eq!(DynWithRegion::SIZE, 0x0c);
+        let periph = unsafe { WithRegion::<Mock<PERIPH>>::steal() };
+        let mut buffer = periph.buffer;
+        buffer.write(1, Value::reset() | Data(0x12));
+        buffer.copy_from_slice(2, &[Value::reset() | Data(0x34); 2]);
+        assert_eq!(buffer.get(4), None);
+        let mut values = [Value::reset(); 3];
+        buffer.copy_to_slice(1, &mut values);
+        let values = values.map(|value| value.field(BUFFER::DATA));
+        assert_eq!(values, [Data(0x12), Data(0x34), Data(0x34)]);
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4001_0006,
+                    width: 2,
+                    value: 0x1200,
+                },
+                Access::Write {
+                    address: 0x4001_0008,
+                    width: 2,
+                    value: 0x3400,
+                },
+                Access::Write {
+                    address: 0x4001_000a,
+                    width: 2,
+                    value: 0x3400,
+                },
+                Access::Read {
+                    address: 0x4001_0006,
+                    width: 2,
+                    value: 0x1200,
+                },
+                Access::Read {
+                    address: 0x4001_0008,
+                    width: 2,
+                    value: 0x3400,
+                },
+                Access::Read {
+                    address: 0x4001_000a,
+                    width: 2,
+                    value: 0x3400,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic = "index out of bounds: BUFFER has 4 elements but the index is 4"]
+    fn region_out_of_bounds() {
+        let mut buffer = unsafe { Memory::<region::BUFFER, Mock<PERIPH>>::steal() };
+        buffer.write(4, Value::reset());
+    }
+}
@@ -0,0 +1,371 @@
+use super::*;
+
+/// An ordered list of register accesses, e.g. to initialise a peripheral
+///
+/// A sequence is built from [`Write`], [`Modify`] and [`WaitUntil`] steps. It can be created in a
+/// `const` and doesn't depend on the peripheral instance. Its type parameter is the `Dyn` struct
+/// generated by the [`periph!`] macro, and only registers of this peripheral can be used in its
+/// steps. It is applied with the `apply` method of the peripheral struct, which can also be a
+/// peripheral of the [`mock`](crate::mock) backend to check the accesses made. The steps are
+/// listed, one per line, when the sequence is displayed with `{}`.
+///
+/// ```
+/// use peripherals::{periph, InitSequence, Modify, WaitUntil, Write, Value};
+///
+/// periph! {
+///     MyPeripheral;
+///     rw CONFIG @ 0x00: u16 = 0x0000 {
+///         ENABLE: 0 = struct Enable(bool);
+///         MODE: 1..2 = enum Mode {
+///             A = 0,
+///             B = 1,
+///             C = 2,
+///             D = 3,
+///         }
+///     }
+///     r STATUS @ 0x02: u16 = 0x0000 {
+///         READY: 0 = struct Ready(bool);
+///     }
+/// }
+///
+/// const INIT: InitSequence<DynMyPeripheral> = InitSequence::new(&[
+///     &Write::<CONFIG>::new(Value::reset()),
+///     &Modify::<CONFIG, _>::new(Mode::C),
+///     &WaitUntil::<STATUS, _>::new(Ready(true), 1000),
+///     &Modify::<CONFIG, _>::new(Enable(true)),
+/// ]);
+///
+/// assert_eq!(INIT.to_string(), "\
+/// write CONFIG @ 0x00: Value<CONFIG>(0x0000)
+/// modify CONFIG @ 0x00: C
+/// wait until STATUS @ 0x02: Ready(true), at most 1000 reads
+/// modify CONFIG @ 0x00: Enable(true)
+/// ");
+/// ```
+///
+/// Registers of other peripherals are rejected:
+///
+/// ```compile_fail
+/// use peripherals::{InitSequence, Write, Value};
+///
+/// mod first {
+///     peripherals::periph! {
+///         First;
+///         rw CONFIG @ 0x00: u16 = 0x0000 {}
+///     }
+/// }
+///
+/// mod second {
+///     peripherals::periph! {
+///         Second;
+///         rw CONFIG @ 0x00: u16 = 0x0000 {}
+///     }
+/// }
+///
+/// const INIT: InitSequence<first::DynFirst> = InitSequence::new(&[
+///     &Write::<second::CONFIG>::new(Value::reset()),
+/// ]);
+/// ```
+pub struct InitSequence<'a, B> {
+    steps: &'a [&'a dyn Step<B>],
+}
+
+impl<'a, B> InitSequence<'a, B> {
+    /// Create a sequence from its steps
+    #[inline]
+    pub const fn new(steps: &'a [&'a dyn Step<B>]) -> InitSequence<'a, B> {
+        InitSequence { steps }
+    }
+
+    /// Get the steps of this sequence
+    #[inline]
+    pub const fn steps(&self) -> &'a [&'a dyn Step<B>] {
+        self.steps
+    }
+}
+
+/// Apply an init sequence to the given peripheral instance
+///
+/// # Safety
+///
+/// The registers accessed by the sequence must not be used at the same time. This is called by
+/// the `apply` method generated by the [`periph!`] macro, which takes the peripheral by mutable
+/// reference.
+#[doc(hidden)]
+#[inline]
+pub unsafe fn apply_sequence<P: Peripheral, B>(sequence: &InitSequence<B>) -> Result<(), TimedOut> {
+    let instance = Instance::<P>::new();
+    for step in sequence.steps {
+        step.apply(&instance)?;
+    }
+    Ok(())
+}
+
+impl<B> Clone for InitSequence<'_, B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B> Copy for InitSequence<'_, B> {}
+
+impl<B> Debug for InitSequence<'_, B> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.steps).finish()
+    }
+}
+
+impl<B> fmt::Display for InitSequence<'_, B> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for step in self.steps {
+            step.describe(fmt)?;
+            fmt.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// A step of an [`InitSequence`]
+///
+/// This trait is implemented by [`Write`], [`Modify`] and [`WaitUntil`], for the peripheral `B` of
+/// their register. It can't be implemented outside of this crate.
+pub trait Step<B>: Apply + Sync {
+    /// Describe this step in a human readable way
+    fn describe(&self, fmt: &mut fmt::Formatter) -> fmt::Result;
+}
+
+impl<B> Debug for dyn Step<B> + '_ {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.describe(fmt)
+    }
+}
+
+fn read<R: Register>(backend: &dyn Backend) -> Value<R::Value> {
+    unsafe { Value::from_raw(read_register::<R, _>(backend, R::OFFSET)) }
+}
+
+fn write<R: Register>(backend: &dyn Backend, value: Value<R::Value>) {
+    write_register::<R, _>(backend, R::OFFSET, value.value());
+}
+
+/// A step writing a value to a register
+pub struct Write<R: Register> {
+    value: R::Int,
+    _reg: PhantomData<fn() -> R>,
+}
+
+impl<R: WriteRegister> Write<R> {
+    /// Create a step writing the given value
+    #[inline]
+    pub const fn new(value: Value<R::Value>) -> Write<R> {
+        Write {
+            value: value.value(),
+            _reg: PhantomData,
+        }
+    }
+
+    #[inline]
+    const fn value(&self) -> Value<R::Value> {
+        unsafe { Value::from_raw(self.value) }
+    }
+}
+
+impl<R: WriteRegister> Apply for Write<R> {
+    #[inline]
+    fn apply(&self, backend: &dyn Backend) -> Result<(), TimedOut> {
+        write::<R>(backend, self.value());
+        Ok(())
+    }
+}
+
+impl<R: WriteRegister + RegisterOf<B>, B> Step<B> for Write<R> {
+    #[inline]
+    fn describe(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "write {} @ 0x{:02x}: {:?}",
+            R::NAME,
+            R::OFFSET,
+            self.value()
+        )
+    }
+}
+
+/// A step modifying fields of a register
+///
+/// The fields are given as anything that converts to [`FieldValues`], e.g. a field value or a
+/// combination of them. It is converted when the step is applied, which allows to create the step
+/// in a `const`.
+pub struct Modify<R, B> {
+    bits: B,
+    _reg: PhantomData<fn() -> R>,
+}
+
+impl<R: ReadRegister + WriteRegister, B: Into<FieldValues<R::Value>> + Copy> Modify<R, B> {
+    /// Create a step modifying the given fields
+    #[inline]
+    pub const fn new(bits: B) -> Modify<R, B> {
+        Modify {
+            bits,
+            _reg: PhantomData,
+        }
+    }
+}
+
+impl<R, B> Apply for Modify<R, B>
+where
+    R: ReadRegister + WriteRegister,
+    B: Into<FieldValues<R::Value>> + Copy + Sync,
+{
+    #[inline]
+    fn apply(&self, backend: &dyn Backend) -> Result<(), TimedOut> {
+        write::<R>(backend, read::<R>(backend) | self.bits);
+        Ok(())
+    }
+}
+
+impl<R, B, P> Step<P> for Modify<R, B>
+where
+    R: ReadRegister + WriteRegister + RegisterOf<P>,
+    B: Into<FieldValues<R::Value>> + Copy + Debug + Sync,
+{
+    #[inline]
+    fn describe(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "modify {} @ 0x{:02x}: {:?}",
+            R::NAME,
+            R::OFFSET,
+            self.bits
+        )
+    }
+}
+
+/// A step waiting until fields of a register have the given values
+///
+/// This is the same as [`Reg::wait_until`], with the maximum number of reads as timeout.
+pub struct WaitUntil<R, B> {
+    bits: B,
+    reads: usize,
+    _reg: PhantomData<fn() -> R>,
+}
+
+impl<R: ReadRegister, B: Into<FieldValues<R::Value>> + Copy> WaitUntil<R, B> {
+    /// Create a step waiting for the given fields, reading the register at most `reads` times
+    ///
+    /// ```compile_fail
+    /// # peripherals::periph!{
+    /// #     MyPeripheral;
+    /// #     r STATUS @ 0x00: u16 = 0x0000 {
+    /// #         READY: 0 = struct Ready(bool);
+    /// #     }
+    /// # }
+    /// use peripherals::WaitUntil;
+    ///
+    /// const WAIT: WaitUntil<STATUS, Ready> = WaitUntil::new(Ready(true), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reads` is 0, which is a compile-time error in a `const`.
+    #[inline]
+    pub const fn new(bits: B, reads: usize) -> WaitUntil<R, B> {
+        if reads == 0 {
+            panic!("a wait step needs at least one read");
+        }
+        WaitUntil {
+            bits,
+            reads,
+            _reg: PhantomData,
+        }
+    }
+}
+
+impl<R, B> Apply for WaitUntil<R, B>
+where
+    R: ReadRegister,
+    B: Into<FieldValues<R::Value>> + Copy + Sync,
+{
+    #[inline]
+    fn apply(&self, backend: &dyn Backend) -> Result<(), TimedOut> {
+        let bits = self.bits.into();
+        let mut timeout = self.reads;
+        loop {
+            if read::<R>(backend).test(bits) {
+                return Ok(());
+            } else if timeout.expired() {
+                return Err(TimedOut);
+            }
+        }
+    }
+}
+
+impl<R, B, P> Step<P> for WaitUntil<R, B>
+where
+    R: ReadRegister + RegisterOf<P>,
+    B: Into<FieldValues<R::Value>> + Copy + Debug + Sync,
+{
+    #[inline]
+    fn describe(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "wait until {} @ 0x{:02x}: {:?}, at most {} reads",
+            R::NAME,
+            R::OFFSET,
+            self.bits,
+            self.reads
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{self, DynPeripheral, Mode, StatFlag, CONFIG, STATUS};
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+
+    #[test]
+    fn init_sequence() {
+        const INIT: InitSequence<DynPeripheral> = InitSequence::new(&[
+            &Write::<CONFIG>::new(Value::reset()),
+            &WaitUntil::<STATUS, _>::new(StatFlag(true), 2),
+            &Modify::<CONFIG, _>::new(Mode::C),
+        ]);
+
+        let mut periph = unsafe { periph::Peripheral::<Mock<PERIPH>>::steal() };
+        assert_eq!(periph.apply(&INIT), Err(TimedOut));
+        assert_eq!(mock::take_log().len(), 3);
+
+        mock::set::<STATUS, PERIPH>(0x0004);
+        assert_eq!(periph.apply(&INIT), Ok(()));
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0000,
+                },
+                Access::Read {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x0004,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0000,
+                },
+                Access::Write {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0002,
+                },
+            ]
+        );
+    }
+}
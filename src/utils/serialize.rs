@@ -0,0 +1,365 @@
+use super::*;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, SerializeMap, SerializeTuple, Serializer};
+use serde::{Deserialize, Serialize};
+
+/// Serialize an integer of the width of a register
+fn serialize_int<I: Int, S: Serializer>(value: I, serializer: S) -> Result<S::Ok, S::Error> {
+    let value = value.to_u128();
+    match I::WIDTH {
+        8 => serializer.serialize_u8(value as u8),
+        16 => serializer.serialize_u16(value as u16),
+        32 => serializer.serialize_u32(value as u32),
+        64 => serializer.serialize_u64(value as u64),
+        _ => serializer.serialize_u128(value),
+    }
+}
+
+/// Deserialize an integer of the width of a register
+fn deserialize_int<'de, I: Int, D: Deserializer<'de>>(deserializer: D) -> Result<I, D::Error> {
+    let visitor = IntVisitor(PhantomData::<I>);
+    match I::WIDTH {
+        8 => deserializer.deserialize_u8(visitor),
+        16 => deserializer.deserialize_u16(visitor),
+        32 => deserializer.deserialize_u32(visitor),
+        64 => deserializer.deserialize_u64(visitor),
+        _ => deserializer.deserialize_u128(visitor),
+    }
+}
+
+/// The maximum value of an integer type
+const fn max<I: Int>() -> u128 {
+    !0 >> (128 - I::WIDTH)
+}
+
+struct IntVisitor<I>(PhantomData<I>);
+
+impl<I: Int> Visitor<'_> for IntVisitor<I> {
+    type Value = I;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "a {}-bit unsigned integer", I::WIDTH)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<I, E> {
+        self.visit_u128(value as u128)
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<I, E> {
+        if value > max::<I>() {
+            return Err(E::invalid_value(de::Unexpected::Other("integer"), &self));
+        }
+        Ok(I::from_u128(value))
+    }
+}
+
+/// The value of a field, serialized as an `u64` if possible
+struct FieldInt(u128);
+
+impl Serialize for FieldInt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0 <= u64::MAX as u128 {
+            serializer.serialize_u64(self.0 as u64)
+        } else {
+            serializer.serialize_u128(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldInt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FieldInt, D::Error> {
+        deserializer
+            .deserialize_u64(IntVisitor(PhantomData::<u128>))
+            .map(FieldInt)
+    }
+}
+
+/// Check that `mask` is made of whole fields
+fn whole_fields<R: RegisterValue, E: ser::Error>(mask: R::Int) -> Result<(), E> {
+    let mask = mask.to_u128();
+    let fields = R::FIELDS
+        .iter()
+        .filter(|field| mask & field.mask() == field.mask())
+        .fold(0, |fields, field| fields | field.mask());
+    if mask == fields {
+        Ok(())
+    } else {
+        Err(E::custom(format_args!(
+            "field values of {} don't cover whole fields",
+            R::NAME
+        )))
+    }
+}
+
+/// Serialize the fields fully included in `mask`, as a map of field names to field values
+fn serialize_fields<R: RegisterValue, S: Serializer>(
+    bits: R::Int,
+    mask: R::Int,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let (bits, mask) = (bits.to_u128(), mask.to_u128());
+    let fields = R::FIELDS
+        .iter()
+        .filter(|field| mask & field.mask() == field.mask());
+    let mut map = serializer.serialize_map(Some(fields.clone().count()))?;
+    for field in fields {
+        let value = (bits & field.mask()) >> field.offset();
+        map.serialize_entry(field.name(), &FieldInt(value))?;
+    }
+    map.end()
+}
+
+/// A field name, deserialized to the description of the field
+struct FieldKey<R>(PhantomData<R>);
+
+impl<'de, R: RegisterValue> DeserializeSeed<'de> for FieldKey<R> {
+    type Value = &'static FieldInfo;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<R: RegisterValue> Visitor<'_> for FieldKey<R> {
+    type Value = &'static FieldInfo;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "a field of {}", R::NAME)
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+        match R::FIELDS.iter().find(|field| field.name() == name) {
+            Some(field) => Ok(field),
+            None => Err(E::custom(format_args!(
+                "unknown field `{}` of {}",
+                name,
+                R::NAME
+            ))),
+        }
+    }
+}
+
+/// Deserialize a map of field names to field values, returning the bits and the mask
+fn deserialize_fields<'de, R: RegisterValue, A: MapAccess<'de>>(
+    mut map: A,
+) -> Result<(u128, u128), A::Error> {
+    let (mut bits, mut mask) = (0, 0);
+    while let Some(field) = map.next_key_seed(FieldKey::<R>(PhantomData))? {
+        let FieldInt(value) = map.next_value()?;
+        if value > field.mask() >> field.offset() {
+            return Err(de::Error::custom(format_args!(
+                "value {} too large for field {} of {}",
+                value,
+                field.name(),
+                R::NAME
+            )));
+        }
+        bits = bits & !field.mask() | value << field.offset();
+        mask |= field.mask();
+    }
+    Ok((bits, mask))
+}
+
+/// Check that each field of a value has a valid value
+fn validate<R: RegisterValue, E: de::Error>(value: R::Int) -> Result<R::Int, E> {
+    if R::is_valid(value) {
+        Ok(value)
+    } else {
+        Err(E::custom(format_args!(
+            "invalid field value for {}",
+            R::NAME
+        )))
+    }
+}
+
+/// Serialized as the raw integer, or as a map of field names to field values in human-readable
+/// formats
+///
+/// Bits outside of fields are not serialized in human-readable formats, and take their reset
+/// value when deserializing. Deserializing checks that each field has a valid value.
+impl<R: RegisterValue> Serialize for Value<R> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && !R::FIELDS.is_empty() {
+            serialize_fields::<R, S>(self.value(), !R::Int::default(), serializer)
+        } else {
+            serialize_int(self.value(), serializer)
+        }
+    }
+}
+
+impl<'de, R: RegisterValue> Deserialize<'de> for Value<R> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value<R>, D::Error> {
+        let value = if deserializer.is_human_readable() && !R::FIELDS.is_empty() {
+            deserializer.deserialize_any(ValueVisitor(PhantomData::<R>))?
+        } else {
+            deserialize_int(deserializer)?
+        };
+        let value = validate::<R, D::Error>(value)?;
+        Ok(unsafe { Value::from_raw(value) })
+    }
+}
+
+struct ValueVisitor<R>(PhantomData<R>);
+
+impl<'de, R: RegisterValue> Visitor<'de> for ValueVisitor<R> {
+    type Value = R::Int;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "a value of {}", R::NAME)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<R::Int, E> {
+        IntVisitor(PhantomData).visit_u64(value)
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<R::Int, E> {
+        IntVisitor(PhantomData).visit_u128(value)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<R::Int, A::Error> {
+        let (bits, mask) = deserialize_fields::<R, A>(map)?;
+        Ok(R::Int::from_u128(R::RESET.to_u128() & !mask | bits))
+    }
+}
+
+/// Serialized as a tuple of the raw bits and mask, or as a map of field names to field values in
+/// human-readable formats
+///
+/// Serializing to human-readable formats fails if the mask covers part of a field or bits outside
+/// of fields. Deserializing checks that each field has a valid value.
+impl<R: RegisterValue, T> Serialize for FieldValues<R, T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            whole_fields::<R, S::Error>(self.mask())?;
+            serialize_fields::<R, S>(self.bits(), self.mask(), serializer)
+        } else {
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&RawInt(self.bits()))?;
+            tuple.serialize_element(&RawInt(self.mask()))?;
+            tuple.end()
+        }
+    }
+}
+
+impl<'de, R: RegisterValue> Deserialize<'de> for FieldValues<R> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FieldValues<R>, D::Error> {
+        let visitor = FieldValuesVisitor(PhantomData::<R>);
+        let (bits, mask) = if deserializer.is_human_readable() {
+            deserializer.deserialize_map(visitor)?
+        } else {
+            deserializer.deserialize_tuple(2, visitor)?
+        };
+        validate::<R, D::Error>(R::RESET & !mask | bits)?;
+        Ok(unsafe { FieldValues::from_raw(bits, mask) })
+    }
+}
+
+/// An integer of the width of a register
+struct RawInt<I>(I);
+
+impl<I: Int> Serialize for RawInt<I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_int(self.0, serializer)
+    }
+}
+
+impl<'de, I: Int> Deserialize<'de> for RawInt<I> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<RawInt<I>, D::Error> {
+        deserialize_int(deserializer).map(RawInt)
+    }
+}
+
+struct FieldValuesVisitor<R>(PhantomData<R>);
+
+impl<'de, R: RegisterValue> Visitor<'de> for FieldValuesVisitor<R> {
+    type Value = (R::Int, R::Int);
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "field values of {}", R::NAME)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let (bits, mask) = deserialize_fields::<R, A>(map)?;
+        Ok((R::Int::from_u128(bits), R::Int::from_u128(mask)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let RawInt::<R::Int>(bits) = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let RawInt::<R::Int>(mask) = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        if bits & !mask != R::Int::default() {
+            return Err(de::Error::custom("bits outside of the mask"));
+        }
+        Ok((bits, mask))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{CfgFlag, Mode, CONFIG};
+
+    mod partial {
+        crate::register! {
+            Partial: u8 = 0x00 {
+                MODE: 0..1 = enum PartialMode {
+                    A = 0,
+                    B = 1,
+                    C = 2,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn serde() {
+        use partial::Partial;
+        use serde_json::{from_str, to_string};
+        use serde_test::{assert_de_tokens_error, assert_tokens, Compact, Configure, Token};
+
+        let value = Value::<CONFIG>::reset() | Mode::C | CfgFlag::True;
+        assert_eq!(to_string(&value).unwrap(), r#"{"MODE":2,"FLAG":1}"#);
+        assert_eq!(
+            from_str::<Value<CONFIG>>(r#"{"FLAG":1,"MODE":2}"#).unwrap(),
+            value
+        );
+        assert_eq!(from_str::<Value<CONFIG>>("6").unwrap(), value);
+        assert!(from_str::<Value<CONFIG>>(r#"{"MODE":4}"#).is_err());
+        assert!(from_str::<Value<CONFIG>>(r#"{"OTHER":1}"#).is_err());
+        assert!(from_str::<Value<Partial>>(r#"{"MODE":3}"#).is_err());
+
+        let fields = Mode::B | CfgFlag::False;
+        assert_eq!(to_string(&fields).unwrap(), r#"{"MODE":1,"FLAG":0}"#);
+        assert_eq!(
+            from_str::<FieldValues<CONFIG>>(r#"{"MODE":1}"#).unwrap(),
+            Mode::B
+        );
+        let partial = unsafe { FieldValues::<CONFIG>::from_raw(0x0001, 0x0001) };
+        assert_eq!(
+            std::format!("{}", to_string(&partial).unwrap_err()),
+            "field values of CONFIG don't cover whole fields"
+        );
+
+        assert_tokens(&value.compact(), &[Token::U16(0x0006)]);
+        assert_tokens(
+            &fields.compact(),
+            &[
+                Token::Tuple { len: 2 },
+                Token::U16(0x0001),
+                Token::U16(0x0007),
+                Token::TupleEnd,
+            ],
+        );
+        assert_de_tokens_error::<Compact<Value<Partial>>>(
+            &[Token::U8(0x03)],
+            "invalid field value for Partial",
+        );
+    }
+}
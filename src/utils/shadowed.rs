@@ -0,0 +1,169 @@
+use super::*;
+
+/// A register with a shadow copy of its last written value
+///
+/// Write-only registers can't be read back, so [`Reg::modify`] and [`Reg::toggle`] are not
+/// available for them. This struct remembers the last value written to the register, and
+/// modifies this copy instead of reading the register. It is created with
+/// [`Reg::into_shadowed`], and starts with the reset value of the register.
+///
+/// The shadow copy is only accurate if the register is written only through this struct, and if
+/// the hardware doesn't change the register by itself.
+///
+/// ```no_run
+/// # peripherals::periph!{
+/// #     MyPeripheral;
+/// #     w CONTROL @ 0x00: u16 = 0x0000 {
+/// #         ENABLE: 0 = struct Enable(bool);
+/// #         MODE: 1..2 = struct Mode(u8);
+/// #     }
+/// # }
+/// # peripherals::device!{
+/// #     MyMicrocontroller;
+/// #     PERIPH @ 0x1234: MyPeripheral;
+/// # }
+/// let periph = MyMicrocontroller::take().unwrap().periph;
+/// let mut control = periph.control.into_shadowed();
+///
+/// control.modify(Mode(2)); // Writes 0b100
+/// control.modify(Enable(true)); // Writes 0b101
+/// control.toggle(CONTROL::ENABLE); // Writes 0b100
+/// assert_eq!(control.value().field(CONTROL::MODE), Mode(2));
+/// ```
+pub struct Shadowed<R: Register, P> {
+    reg: Reg<R, P>,
+    value: Value<R::Value>,
+}
+
+impl<R: WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Keep a shadow copy of the values written to this register
+    ///
+    /// This allows to modify write-only registers. The shadow copy starts with the reset value of
+    /// the register. See [`Shadowed`] for more details.
+    #[inline]
+    pub const fn into_shadowed(self) -> Shadowed<R, P> {
+        Shadowed {
+            reg: self,
+            value: Value::reset(),
+        }
+    }
+}
+
+impl<R: WriteRegister, P: Peripheral> Shadowed<R, P> {
+    /// Create a shadowed register whose last written value is known
+    ///
+    /// This is useful when the register was written before the shadow copy was created.
+    #[inline]
+    pub const fn with_value(reg: Reg<R, P>, value: Value<R::Value>) -> Shadowed<R, P> {
+        Shadowed { reg, value }
+    }
+
+    /// Get the last value written to the register
+    #[inline]
+    pub const fn value(&self) -> Value<R::Value> {
+        self.value
+    }
+
+    /// Stop keeping a shadow copy, returning the register
+    #[inline]
+    pub const fn into_inner(self) -> Reg<R, P> {
+        self.reg
+    }
+
+    /// Write a value to this register
+    ///
+    /// Same as [`Reg::write`], and also updates the shadow copy.
+    #[inline]
+    pub fn write(&mut self, value: Value<R::Value>) {
+        self.value = value;
+        self.reg.write(value);
+    }
+
+    /// Reset this register
+    ///
+    /// Same as [`Reg::reset`], and also updates the shadow copy.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.write(Value::reset());
+    }
+
+    /// Write a value computed from the reset value
+    ///
+    /// Same as [`Reg::write_with`], and also updates the shadow copy.
+    #[inline]
+    pub fn write_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(Value::reset()));
+    }
+
+    /// Modify the given fields
+    ///
+    /// Same as [`Reg::modify`], but the other fields keep their last written values.
+    #[inline]
+    pub fn modify<B: Into<FieldValues<R::Value>>>(&mut self, bits: B) {
+        self.write(self.value | bits);
+    }
+
+    /// Modify this register with a closure
+    ///
+    /// Same as [`Reg::modify_with`], but the closure receives the last written value.
+    #[inline]
+    pub fn modify_with<F: FnOnce(Value<R::Value>) -> Value<R::Value>>(&mut self, f: F) {
+        self.write(f(self.value));
+    }
+
+    /// Toggle the given fields
+    ///
+    /// Same as [`Reg::toggle`], but toggles the last written values of the fields.
+    #[inline]
+    pub fn toggle<F: Into<Fields<R::Value, Toggle>>>(&mut self, fields: F) {
+        let fields = fields.into();
+        self.write(self.value ^ fields);
+    }
+}
+
+impl<R: Register, P: Peripheral> Debug for Shadowed<R, P> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Shadowed")
+            .field("reg", &self.reg)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{Data, BUFFER};
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+
+    #[test]
+    fn shadowed() {
+        let buffer = unsafe { Reg::<BUFFER, Mock<PERIPH>>::steal() };
+        let mut buffer = buffer.into_shadowed();
+        buffer.modify(Data(0x12));
+        buffer.modify_with(|value| value | Data(value.field(BUFFER::DATA).0 + 1));
+        assert_eq!(buffer.value().value(), 0x0013);
+        buffer.reset();
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Write {
+                    address: 0x4001_0004,
+                    width: 2,
+                    value: 0x12,
+                },
+                Access::Write {
+                    address: 0x4001_0004,
+                    width: 2,
+                    value: 0x13,
+                },
+                Access::Write {
+                    address: 0x4001_0004,
+                    width: 2,
+                    value: 0x00,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use super::*;
+
+/// A field that differs between two snapshots of a peripheral
+///
+/// This is returned by the `diff` method of the snapshot struct generated by the [`periph!`]
+/// macro. The values are the raw values of the field, shifted to start at bit 0. Registers without
+/// fields are compared as a whole, with an empty field name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The name of the register
+    pub register: &'static str,
+    /// The name of the field, or an empty string for registers without fields
+    pub field: &'static str,
+    /// The value of the field in the first snapshot
+    pub old: u128,
+    /// The value of the field in the second snapshot
+    pub new: u128,
+}
+
+/// Iterator over the fields that differ between two values of a register
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub struct FieldChanges {
+    register: &'static str,
+    fields: core::slice::Iter<'static, FieldInfo>,
+    whole: bool,
+    old: u128,
+    new: u128,
+}
+
+impl FieldChanges {
+    /// Compare two values of a register
+    #[inline]
+    pub fn new<R: Register>(old: Value<R::Value>, new: Value<R::Value>) -> FieldChanges {
+        let fields = <R::Value as RegisterValue>::FIELDS;
+        FieldChanges {
+            register: R::NAME,
+            fields: fields.iter(),
+            whole: fields.is_empty(),
+            old: old.value().to_u128(),
+            new: new.value().to_u128(),
+        }
+    }
+}
+
+/// Chain the changes of all registers of a peripheral
+#[doc(hidden)]
+#[inline]
+pub fn field_changes<const N: usize>(
+    changes: [FieldChanges; N],
+) -> impl Iterator<Item = FieldChange> {
+    IntoIterator::into_iter(changes).flatten()
+}
+
+impl Iterator for FieldChanges {
+    type Item = FieldChange;
+
+    #[inline]
+    fn next(&mut self) -> Option<FieldChange> {
+        let (register, old, new) = (self.register, self.old, self.new);
+        if self.whole {
+            self.whole = false;
+            return if old != new {
+                Some(FieldChange {
+                    register,
+                    field: "",
+                    old,
+                    new,
+                })
+            } else {
+                None
+            };
+        }
+        self.fields.find_map(|field| {
+            let old = (old & field.mask()) >> field.offset();
+            let new = (new & field.mask()) >> field.offset();
+            if old != new {
+                Some(FieldChange {
+                    register,
+                    field: field.name(),
+                    old,
+                    new,
+                })
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{self, CONFIG, STATUS};
+    use crate::utils::mock::{self, Access, Mock, PERIPH};
+    use std::vec::Vec;
+
+    #[test]
+    fn snapshot() {
+        let mut periph = unsafe { periph::Peripheral::<Mock<PERIPH>>::steal() };
+        mock::set::<CONFIG, PERIPH>(0x0001);
+        mock::set::<STATUS, PERIPH>(0x0004);
+        let saved = periph.snapshot();
+        assert_eq!(saved.config.value(), 0x0001);
+        assert_eq!(saved.status.value(), 0x0004);
+
+        mock::set::<CONFIG, PERIPH>(0x0006);
+        mock::set::<STATUS, PERIPH>(0x0000);
+        let changes: Vec<_> = saved.diff(&periph.snapshot()).collect();
+        assert_eq!(
+            changes,
+            [
+                FieldChange {
+                    register: "CONFIG",
+                    field: "MODE",
+                    old: 1,
+                    new: 2,
+                },
+                FieldChange {
+                    register: "CONFIG",
+                    field: "FLAG",
+                    old: 0,
+                    new: 1,
+                },
+                FieldChange {
+                    register: "STATUS",
+                    field: "FLAG",
+                    old: 1,
+                    new: 0,
+                },
+            ]
+        );
+
+        mock::take_log();
+        periph.restore(&saved);
+        assert_eq!(
+            mock::take_log(),
+            [Access::Write {
+                address: 0x4001_0000,
+                width: 2,
+                value: 0x0001,
+            }]
+        );
+
+        mock::set::<CONFIG, PERIPH>(0x0006);
+        periph.restore_config(&saved);
+        assert_eq!(mock::get::<CONFIG, PERIPH>().value(), 0x0001);
+    }
+
+    #[allow(dead_code, clippy::upper_case_acronyms)]
+    mod without_fields {
+        crate::periph! {
+            WithoutFields;
+            rw CONFIG @ 0x00: u16 = 0x0000 {}
+            r SHUNT @ 0x02: u16 = 0x0000 {}
+        }
+    }
+
+    #[test]
+    fn snapshot_without_fields() {
+        use without_fields::{WithoutFields, CONFIG, SHUNT};
+
+        let periph = unsafe { WithoutFields::<Mock<PERIPH>>::steal() };
+        mock::set::<CONFIG, PERIPH>(0x0001);
+        mock::set::<SHUNT, PERIPH>(0x1234);
+        let saved = periph.snapshot();
+
+        mock::set::<SHUNT, PERIPH>(0x1235);
+        let changes: Vec<_> = saved.diff(&periph.snapshot()).collect();
+        assert_eq!(
+            changes,
+            [FieldChange {
+                register: "SHUNT",
+                field: "",
+                old: 0x1234,
+                new: 0x1235,
+            }]
+        );
+        assert_eq!(
+            mock::take_log(),
+            [
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+                Access::Read {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x1234,
+                },
+                Access::Read {
+                    address: 0x4001_0000,
+                    width: 2,
+                    value: 0x0001,
+                },
+                Access::Read {
+                    address: 0x4001_0002,
+                    width: 2,
+                    value: 0x1235,
+                },
+            ]
+        );
+    }
+}
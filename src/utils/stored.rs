@@ -0,0 +1,245 @@
+use super::*;
+
+/// A value with register fields, stored in normal memory
+///
+/// Some data in RAM has the same layout needs as registers, e.g. DMA descriptors, packet headers
+/// or flash option bytes. Their layout is defined with the [`register!`] macro, and they are
+/// stored as a `Stored`, which has the same representation as the underlying integer and can be
+/// embedded in other structs. Unlike [`Reg`], accesses are not volatile.
+///
+/// It converts to and from [`Value`], and supports the same operators.
+///
+/// ```
+/// use core::mem::size_of;
+/// use peripherals::{register, Stored, Value};
+///
+/// register! {
+///     Control: u32 = 0x0000_0000 {
+///         LEN: 0..15 = struct Len(u16);
+///         LAST: 30 = struct Last(bool);
+///         OWN: 31 = struct Own(bool);
+///     }
+/// }
+///
+/// #[repr(C)]
+/// struct Descriptor {
+///     control: Stored<Control>,
+///     buffer: u32,
+/// }
+///
+/// assert_eq!(size_of::<Descriptor>(), 8);
+///
+/// let mut descriptor = Descriptor {
+///     control: Stored::reset() | Len(64),
+///     buffer: 0x2000_0000,
+/// };
+/// descriptor.control |= Last(true) | Own(true);
+/// assert_eq!(descriptor.control.field(Control::LEN), Len(64));
+///
+/// let value: Value<Control> = descriptor.control.into();
+/// assert_eq!(value.value(), 0xc000_0040);
+/// ```
+#[repr(transparent)]
+pub struct Stored<R: RegisterValue> {
+    value: R::Int,
+    _reg: PhantomData<R>,
+}
+
+impl<R: RegisterValue> Stored<R> {
+    /// Store a value
+    #[inline]
+    pub const fn new(value: Value<R>) -> Stored<R> {
+        Stored {
+            value: value.value(),
+            _reg: PhantomData,
+        }
+    }
+
+    /// Get the stored value
+    #[inline]
+    pub const fn get(self) -> Value<R> {
+        unsafe { Value::from_raw(self.value) }
+    }
+
+    /// Replace the stored value
+    #[inline]
+    pub fn set(&mut self, value: Value<R>) {
+        self.value = value.value();
+    }
+
+    /// Modify the given fields
+    ///
+    /// Same as `stored |= bits`.
+    #[inline]
+    pub fn modify<B: Into<FieldValues<R>>>(&mut self, bits: B) {
+        *self |= bits;
+    }
+
+    /// Get the raw value
+    #[inline]
+    pub const fn value(self) -> R::Int {
+        self.value
+    }
+
+    /// Build from a raw value
+    ///
+    /// # Safety
+    ///
+    /// You must ensure the value is valid for the associated register.
+    #[inline]
+    pub const unsafe fn from_raw(value: R::Int) -> Stored<R> {
+        Stored {
+            value,
+            _reg: PhantomData,
+        }
+    }
+
+    /// Read the given field
+    ///
+    /// See [`Value::field`].
+    #[inline]
+    pub fn field<T>(self, field: Field<R, T, R::Int>) -> T
+    where
+        R::Int: TryInto<T>,
+        <R::Int as TryInto<T>>::Error: Debug,
+    {
+        self.get().field(field)
+    }
+
+    /// Test the given fields
+    ///
+    /// See [`Value::test`].
+    #[inline]
+    pub fn test<B: Into<FieldValues<R>>>(self, bits: B) -> bool {
+        self.get().test(bits)
+    }
+
+    /// Get the default / reset value
+    #[inline]
+    pub const fn reset() -> Stored<R> {
+        Stored::new(Value::reset())
+    }
+}
+
+impl<R: RegisterValue> From<Value<R>> for Stored<R> {
+    #[inline]
+    fn from(value: Value<R>) -> Stored<R> {
+        Stored::new(value)
+    }
+}
+
+impl<R: RegisterValue> From<Stored<R>> for Value<R> {
+    #[inline]
+    fn from(stored: Stored<R>) -> Value<R> {
+        stored.get()
+    }
+}
+
+impl<R: RegisterValue> Clone for Stored<R> {
+    #[inline]
+    fn clone(&self) -> Stored<R> {
+        *self
+    }
+}
+
+impl<R: RegisterValue> Copy for Stored<R> {}
+
+impl<R: RegisterValue> Default for Stored<R> {
+    #[inline]
+    fn default() -> Stored<R> {
+        Stored::reset()
+    }
+}
+
+impl<R: RegisterValue> Debug for Stored<R> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if fmt.alternate() {
+            write!(
+                fmt,
+                "Stored<{}>(0b{:02$b})",
+                R::NAME,
+                self.value,
+                <R::Int as Int>::WIDTH
+            )
+        } else {
+            write!(
+                fmt,
+                "Stored<{}>(0x{:02$x})",
+                R::NAME,
+                self.value,
+                <R::Int as Int>::WIDTH / 4
+            )
+        }
+    }
+}
+
+impl<R: RegisterValue, T: Into<Value<R>> + Copy> PartialEq<T> for Stored<R> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.get() == *other
+    }
+}
+
+impl<R: RegisterValue> Eq for Stored<R> {}
+
+impl<R: RegisterValue, T: Into<FieldValues<R>>> BitOr<T> for Stored<R> {
+    type Output = Stored<R>;
+
+    #[inline]
+    fn bitor(self, other: T) -> Self::Output {
+        Stored::new(self.get() | other)
+    }
+}
+
+impl<R: RegisterValue, T: Into<FieldValues<R>>> BitOrAssign<T> for Stored<R> {
+    #[inline]
+    fn bitor_assign(&mut self, other: T) {
+        self.set(self.get() | other);
+    }
+}
+
+impl<R: RegisterValue, T: Into<Fields<R>> + MayToggle> BitAnd<T> for Stored<R> {
+    type Output = FieldValues<R, T::Toggle>;
+
+    #[inline]
+    fn bitand(self, other: T) -> Self::Output {
+        self.get() & other
+    }
+}
+
+impl<R: RegisterValue, T: Into<Fields<R, Toggle>>> BitXor<T> for Stored<R> {
+    type Output = Stored<R>;
+
+    #[inline]
+    fn bitxor(self, other: T) -> Self::Output {
+        Stored::new(self.get() ^ other)
+    }
+}
+
+impl<R: RegisterValue, T: Into<Fields<R, Toggle>>> BitXorAssign<T> for Stored<R> {
+    #[inline]
+    fn bitxor_assign(&mut self, other: T) {
+        self.set(self.get() ^ other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{CfgFlag, Mode, CONFIG};
+
+    #[test]
+    fn stored() {
+        assert_eq!(core::mem::size_of::<Stored<CONFIG>>(), 2);
+        let mut stored = Stored::<CONFIG>::reset() | Mode::C;
+        stored ^= CONFIG::FLAG;
+        assert_eq!(stored.value(), 0x0006);
+        assert!(stored.test(Mode::C | CfgFlag::True));
+        assert_eq!(stored & CONFIG::MODE, Mode::C);
+        let value = Value::from(stored) | Mode::A;
+        stored.set(value);
+        assert_eq!(stored, value);
+        assert_eq!(std::format!("{:?}", stored), "Stored<CONFIG>(0x0004)");
+    }
+}
@@ -0,0 +1,60 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag that can be taken only once
+///
+/// This is used by the [`device!`] macro to ensure there is a single instance of the device.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Taken(AtomicBool);
+
+impl Taken {
+    /// Create a new flag, not taken yet
+    #[inline]
+    pub const fn new() -> Taken {
+        Taken(AtomicBool::new(false))
+    }
+
+    /// Take the flag, returning `true` if it wasn't taken before
+    #[inline]
+    #[cfg(target_has_atomic = "8")]
+    pub fn take(&self) -> bool {
+        !self.0.swap(true, Ordering::AcqRel)
+    }
+
+    /// Take the flag, returning `true` if it wasn't taken before
+    ///
+    /// This target doesn't support atomic swap, so this is done inside a critical section.
+    #[inline]
+    #[cfg(all(not(target_has_atomic = "8"), feature = "critical-section"))]
+    pub fn take(&self) -> bool {
+        critical_section::with(|_| {
+            let taken = self.0.load(Ordering::Acquire);
+            self.0.store(true, Ordering::Release);
+            !taken
+        })
+    }
+
+    /// Take the flag, returning `true` if it wasn't taken before
+    ///
+    /// This target doesn't support atomic swap. This isn't atomic with respect to interrupts.
+    #[inline]
+    #[cfg(all(not(target_has_atomic = "8"), not(feature = "critical-section")))]
+    pub fn take(&self) -> bool {
+        let taken = self.0.load(Ordering::Acquire);
+        self.0.store(true, Ordering::Release);
+        !taken
+    }
+
+    /// Mark the flag as taken
+    #[inline]
+    pub fn set(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+impl Default for Taken {
+    #[inline]
+    fn default() -> Taken {
+        Taken::new()
+    }
+}
@@ -0,0 +1,44 @@
+/// A limit when waiting for a register to have some value
+///
+/// This is used by [`Reg::wait_until`](crate::Reg::wait_until) and
+/// [`Reg::wait_while`](crate::Reg::wait_while). It is implemented for:
+/// - `usize`: the maximum number of times the register is read.
+/// - Closures returning `bool`: they are called after each read, and should return `true` once
+///   the deadline is reached, e.g. by checking a timer.
+///
+/// ```
+/// use peripherals::Timeout;
+///
+/// let mut reads = 3;
+/// assert!(!reads.expired());
+/// assert!(!reads.expired());
+/// assert!(reads.expired());
+///
+/// let mut deadline = || true;
+/// assert!(deadline.expired());
+/// ```
+pub trait Timeout {
+    /// Check whether to stop waiting
+    ///
+    /// This is called after each read that doesn't have the expected value.
+    fn expired(&mut self) -> bool;
+}
+
+impl Timeout for usize {
+    #[inline]
+    fn expired(&mut self) -> bool {
+        *self = self.saturating_sub(1);
+        *self == 0
+    }
+}
+
+impl<F: FnMut() -> bool> Timeout for F {
+    #[inline]
+    fn expired(&mut self) -> bool {
+        self()
+    }
+}
+
+/// Error returned when waiting for a register times out
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimedOut;
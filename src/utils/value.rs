@@ -55,7 +55,6 @@ use super::*;
 /// value |= TwoBits(0b01);
 /// assert_eq!(value.value(), 0b0100);
 /// ```
-
 pub struct Value<R: RegisterValue> {
     value: R::Int,
     _reg: PhantomData<R>,
@@ -64,7 +63,7 @@ pub struct Value<R: RegisterValue> {
 impl<R: RegisterValue> Value<R> {
     /// Get the raw value
     #[inline]
-    pub fn value(self) -> R::Int {
+    pub const fn value(self) -> R::Int {
         self.value
     }
 
@@ -74,7 +73,7 @@ impl<R: RegisterValue> Value<R> {
     ///
     /// You must ensure the value is valid for the associated register.
     #[inline]
-    pub unsafe fn from_raw(value: R::Int) -> Value<R> {
+    pub const unsafe fn from_raw(value: R::Int) -> Value<R> {
         Value {
             value,
             _reg: PhantomData,
@@ -105,11 +104,76 @@ impl<R: RegisterValue> Value<R> {
         self.value & bits.mask() == bits.bits()
     }
 
+    /// Build from a raw value, checking that each field has a valid value
+    ///
+    /// This returns [`InvalidValue`] if a field can't be converted to its type, e.g. if an enum
+    /// field has no variant for its value.
+    #[inline]
+    pub fn try_from_raw(value: R::Int) -> Result<Value<R>, InvalidValue> {
+        if R::is_valid(value) {
+            Ok(Value {
+                value,
+                _reg: PhantomData,
+            })
+        } else {
+            Err(InvalidValue)
+        }
+    }
+
+    /// Get the raw value as bytes in little-endian byte order
+    ///
+    /// ```
+    /// use peripherals::{register, Value};
+    ///
+    /// register! {
+    ///     Register: u16 = 0x0000 {
+    ///         MODE: 0..1 = enum Mode {
+    ///             A = 0,
+    ///             B = 1,
+    ///             C = 2,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let value = Value::<Register>::reset() | Mode::C;
+    /// assert_eq!(value.to_le_bytes(), [0x02, 0x00]);
+    /// assert_eq!(value.to_be_bytes(), [0x00, 0x02]);
+    /// assert_eq!(Value::<Register>::from_le_bytes([0x02, 0x00]), Ok(value));
+    /// // There is no variant for 3
+    /// assert!(Value::<Register>::from_le_bytes([0x03, 0x00]).is_err());
+    /// ```
+    #[inline]
+    pub fn to_le_bytes(self) -> Bytes<R> {
+        self.value.to_le_bytes()
+    }
+
+    /// Get the raw value as bytes in big-endian byte order
+    #[inline]
+    pub fn to_be_bytes(self) -> Bytes<R> {
+        self.value.swap_bytes().to_le_bytes()
+    }
+
+    /// Build from bytes in little-endian byte order, checking that each field has a valid value
+    ///
+    /// See [`Value::try_from_raw`].
+    #[inline]
+    pub fn from_le_bytes(bytes: Bytes<R>) -> Result<Value<R>, InvalidValue> {
+        Value::try_from_raw(R::Int::from_le_bytes(bytes))
+    }
+
+    /// Build from bytes in big-endian byte order, checking that each field has a valid value
+    ///
+    /// See [`Value::try_from_raw`].
+    #[inline]
+    pub fn from_be_bytes(bytes: Bytes<R>) -> Result<Value<R>, InvalidValue> {
+        Value::try_from_raw(R::Int::from_le_bytes(bytes).swap_bytes())
+    }
+
     /// Get the default / reset value
     ///
     /// This returns to the value that the register has right right after a reset or a boot.
     #[inline]
-    pub fn reset() -> Value<R> {
+    pub const fn reset() -> Value<R> {
         Value {
             value: R::RESET,
             _reg: PhantomData,
@@ -120,10 +184,7 @@ impl<R: RegisterValue> Value<R> {
 impl<R: RegisterValue> Clone for Value<R> {
     #[inline]
     fn clone(&self) -> Value<R> {
-        Value {
-            value: self.value,
-            _reg: PhantomData,
-        }
+        *self
     }
 }
 
@@ -140,6 +201,7 @@ impl<R: RegisterValue> Default for Value<R> {
 }
 
 impl<R: RegisterValue> Debug for Value<R> {
+    #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         if fmt.alternate() {
             write!(
@@ -0,0 +1,196 @@
+use super::*;
+
+/// Error returned when a register doesn't have the value that was written to it
+///
+/// This is returned by [`Reg::write_verified`] and similar methods. Both values contain all the
+/// bits of the register, including the ones that were not compared.
+pub struct VerifyError<R: RegisterValue> {
+    /// The value that was written
+    pub expected: Value<R>,
+    /// The value that was read back
+    pub observed: Value<R>,
+}
+
+impl<R: RegisterValue> VerifyError<R> {
+    /// Check that the compared bits of two values are equal
+    #[inline]
+    fn check(expected: Value<R>, observed: Value<R>, ignore: Fields<R>) -> Result<(), Self> {
+        let mask = !ignore.mask();
+        if expected.value() & mask == observed.value() & mask {
+            Ok(())
+        } else {
+            Err(VerifyError { expected, observed })
+        }
+    }
+}
+
+impl<R: RegisterValue> Clone for VerifyError<R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: RegisterValue> Copy for VerifyError<R> {}
+
+impl<R: RegisterValue> PartialEq for VerifyError<R> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.expected == other.expected && self.observed == other.observed
+    }
+}
+
+impl<R: RegisterValue> Eq for VerifyError<R> {}
+
+impl<R: RegisterValue> Debug for VerifyError<R> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("VerifyError")
+            .field("expected", &self.expected)
+            .field("observed", &self.observed)
+            .finish()
+    }
+}
+
+impl<R: ReadRegister + WriteRegister, P: Peripheral> Reg<R, P> {
+    /// Write a value to this register and check that it took effect
+    ///
+    /// The register is read back after the write, and all its bits are compared with the written
+    /// value. Use [`Reg::write_verified_except`] to ignore some fields.
+    ///
+    /// See [`DynReg::write_verified`] for an example.
+    #[inline]
+    pub fn write_verified(&mut self, value: Value<R::Value>) -> Result<(), VerifyError<R::Value>> {
+        self.write_verified_except(value, Fields::<R::Value>::empty())
+    }
+
+    /// Write a value to this register and check that it took effect, except for some fields
+    ///
+    /// Same as [`Reg::write_verified`], but the bits of the `ignore` fields are not compared.
+    /// This allows to ignore e.g. status flags that may change at any time.
+    #[inline]
+    pub fn write_verified_except<F: Into<Fields<R::Value>>>(
+        &mut self,
+        value: Value<R::Value>,
+        ignore: F,
+    ) -> Result<(), VerifyError<R::Value>> {
+        self.write(value);
+        VerifyError::check(value, self.read(), ignore.into())
+    }
+
+    /// Modify the given fields and check that it took effect
+    ///
+    /// Same as [`Reg::modify`], but the register is read back after the write. See
+    /// [`Reg::write_verified`] for more details.
+    #[inline]
+    pub fn modify_verified<B: Into<FieldValues<R::Value>>>(
+        &mut self,
+        bits: B,
+    ) -> Result<(), VerifyError<R::Value>> {
+        self.modify_verified_except(bits, Fields::<R::Value>::empty())
+    }
+
+    /// Modify the given fields and check that it took effect, except for some fields
+    ///
+    /// Same as [`Reg::modify_verified`], but the bits of the `ignore` fields are not compared.
+    #[inline]
+    pub fn modify_verified_except<B: Into<FieldValues<R::Value>>, F: Into<Fields<R::Value>>>(
+        &mut self,
+        bits: B,
+        ignore: F,
+    ) -> Result<(), VerifyError<R::Value>> {
+        let value = self.read() | bits;
+        self.write_verified_except(value, ignore)
+    }
+}
+
+impl<R: ReadRegister + WriteRegister> DynReg<R> {
+    /// Write a value to this register and check that it took effect
+    ///
+    /// The register is read back after the write, and all its bits are compared with the written
+    /// value. Use [`DynReg::write_verified_except`] to ignore some fields.
+    ///
+    /// ```
+    /// use peripherals::{periph, Value};
+    ///
+    /// periph! {
+    ///     MyPeripheral;
+    ///     rw CONFIG @ 0x00: u16 = 0x0000 {
+    ///         ENABLE: 0 = struct Enable(bool);
+    ///         BUSY: 1 = struct Busy(bool);
+    ///     }
+    /// }
+    ///
+    /// let mut memory = 0u16;
+    /// let periph = unsafe { &mut *(&mut memory as *mut u16 as *mut DynMyPeripheral) };
+    ///
+    /// assert!(periph.config.write_verified(Value::reset() | Enable(true)).is_ok());
+    /// assert!(periph.config.modify_verified_except(Busy(true), CONFIG::BUSY).is_ok());
+    /// ```
+    #[inline]
+    pub fn write_verified(&mut self, value: Value<R::Value>) -> Result<(), VerifyError<R::Value>> {
+        self.write_verified_except(value, Fields::<R::Value>::empty())
+    }
+
+    /// Write a value to this register and check that it took effect, except for some fields
+    ///
+    /// Same as [`DynReg::write_verified`], but the bits of the `ignore` fields are not compared.
+    /// This allows to ignore e.g. status flags that may change at any time.
+    #[inline]
+    pub fn write_verified_except<F: Into<Fields<R::Value>>>(
+        &mut self,
+        value: Value<R::Value>,
+        ignore: F,
+    ) -> Result<(), VerifyError<R::Value>> {
+        self.write(value);
+        VerifyError::check(value, self.read(), ignore.into())
+    }
+
+    /// Modify the given fields and check that it took effect
+    ///
+    /// Same as [`DynReg::modify`], but the register is read back after the write. See
+    /// [`DynReg::write_verified`] for more details.
+    #[inline]
+    pub fn modify_verified<B: Into<FieldValues<R::Value>>>(
+        &mut self,
+        bits: B,
+    ) -> Result<(), VerifyError<R::Value>> {
+        self.modify_verified_except(bits, Fields::<R::Value>::empty())
+    }
+
+    /// Modify the given fields and check that it took effect, except for some fields
+    ///
+    /// Same as [`DynReg::modify_verified`], but the bits of the `ignore` fields are not compared.
+    #[inline]
+    pub fn modify_verified_except<B: Into<FieldValues<R::Value>>, F: Into<Fields<R::Value>>>(
+        &mut self,
+        bits: B,
+        ignore: F,
+    ) -> Result<(), VerifyError<R::Value>> {
+        let value = self.read() | bits;
+        self.write_verified_except(value, ignore)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{CfgFlag, Mode, CONFIG};
+    use crate::utils::mock::{self, Mock, PERIPH};
+
+    #[test]
+    fn verified() {
+        let mut config = unsafe { Reg::<CONFIG, Mock<PERIPH>>::steal() };
+        // The flag is stuck at 0
+        mock::on_read::<CONFIG, PERIPH, _>(|value| value | CfgFlag::False);
+
+        assert_eq!(config.write_verified(Value::reset() | Mode::B), Ok(()));
+        let error = config.modify_verified(CfgFlag::True).unwrap_err();
+        assert_eq!(error.expected.value(), 0x0005);
+        assert_eq!(error.observed.value(), 0x0001);
+        assert_eq!(
+            config.modify_verified_except(CfgFlag::True, CONFIG::FLAG),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use super::*;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+/// A trait for peripheral instances whose registers can be awaited
+///
+/// This trait is implemented by the [`device!`] macro for peripheral instances declared with the
+/// `poll` or `waker` option, and allows to use [`Reg::wait_for`].
+///
+/// The default implementation of [`register`](WakerRegistry::register) wakes the task
+/// immediately, so that the register is polled each time the executor runs the task. With the
+/// `waker` option, the waker is instead stored in an [`AtomicWaker`], which should be woken by the
+/// interrupt handler of the peripheral.
+pub trait WakerRegistry: Peripheral {
+    /// Register the waker of a task waiting on a register of this peripheral
+    #[inline]
+    fn register(waker: &Waker) {
+        waker.wake_by_ref();
+    }
+}
+
+/// Future returned by [`Reg::wait_for`]
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitFor<'a, R: Register, P: Peripheral> {
+    reg: &'a Reg<R, P>,
+    bits: FieldValues<R::Value>,
+}
+
+impl<'a, R: Register, P: Peripheral> WaitFor<'a, R, P> {
+    #[inline]
+    pub(crate) const fn new(reg: &'a Reg<R, P>, bits: FieldValues<R::Value>) -> Self {
+        WaitFor { reg, bits }
+    }
+}
+
+impl<R: ReadRegister, P: WakerRegistry> Future for WaitFor<'_, R, P> {
+    type Output = Value<R::Value>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Value<R::Value>> {
+        // Register first, so that a wake between the read and returning isn't missed
+        P::register(cx.waker());
+        let value = self.reg.read();
+        if value.test(self.bits) {
+            Poll::Ready(value)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<R: Register, P: Peripheral> Debug for WaitFor<'_, R, P> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitFor")
+            .field("reg", self.reg)
+            .field("bits", &self.bits)
+            .finish()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub use atomic::AtomicWaker;
+#[cfg(all(not(target_has_atomic = "ptr"), feature = "critical-section"))]
+pub use cs::AtomicWaker;
+
+#[cfg(target_has_atomic = "ptr")]
+mod atomic {
+    use core::cell::UnsafeCell;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::Waker;
+
+    const WAITING: usize = 0;
+    const REGISTERING: usize = 1;
+    const WAKING: usize = 2;
+
+    /// A waker that can be registered by a task and woken from an interrupt handler
+    ///
+    /// This is used by the [`device!`] macro for peripheral instances declared with the `waker`
+    /// option. On targets without atomic compare-and-swap, this requires the `critical-section`
+    /// feature.
+    pub struct AtomicWaker {
+        state: AtomicUsize,
+        waker: UnsafeCell<Option<Waker>>,
+    }
+
+    // Safety: the waker is only accessed by the thread that moved the state out of `WAITING`
+    unsafe impl Send for AtomicWaker {}
+    unsafe impl Sync for AtomicWaker {}
+
+    impl AtomicWaker {
+        /// Create a new waker, with no task registered
+        #[inline]
+        pub const fn new() -> AtomicWaker {
+            AtomicWaker {
+                state: AtomicUsize::new(WAITING),
+                waker: UnsafeCell::new(None),
+            }
+        }
+
+        /// Register the waker of a task, replacing the previous one
+        #[inline]
+        pub fn register(&self, waker: &Waker) {
+            match self.state.compare_exchange(
+                WAITING,
+                REGISTERING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // Safety: the `REGISTERING` state gives exclusive access to the waker
+                    let slot = unsafe { &mut *self.waker.get() };
+                    match slot {
+                        Some(old) if old.will_wake(waker) => {}
+                        _ => *slot = Some(waker.clone()),
+                    }
+
+                    if self
+                        .state
+                        .compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire)
+                        .is_err()
+                    {
+                        // `wake` was called while registering
+                        let waker = slot.take();
+                        self.state.store(WAITING, Ordering::Release);
+                        if let Some(waker) = waker {
+                            waker.wake();
+                        }
+                    }
+                }
+                Err(WAKING) => waker.wake_by_ref(),
+                // Concurrent calls to `register`, one of them wins
+                Err(_) => {}
+            }
+        }
+
+        /// Wake the registered task, if any
+        #[inline]
+        pub fn wake(&self) {
+            if self.state.fetch_or(WAKING, Ordering::AcqRel) == WAITING {
+                // Safety: the `WAKING` state gives exclusive access to the waker
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state.fetch_and(!WAKING, Ordering::Release);
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    impl Default for AtomicWaker {
+        #[inline]
+        fn default() -> AtomicWaker {
+            AtomicWaker::new()
+        }
+    }
+
+    impl core::fmt::Debug for AtomicWaker {
+        #[inline]
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("AtomicWaker")
+        }
+    }
+}
+
+#[cfg(all(not(target_has_atomic = "ptr"), feature = "critical-section"))]
+mod cs {
+    use core::cell::RefCell;
+    use core::task::Waker;
+    use critical_section::Mutex;
+
+    /// A waker that can be registered by a task and woken from an interrupt handler
+    ///
+    /// This is used by the [`device!`] macro for peripheral instances declared with the `waker`
+    /// option. This target doesn't support atomic compare-and-swap, so the waker is accessed
+    /// inside a critical section.
+    pub struct AtomicWaker(Mutex<RefCell<Option<Waker>>>);
+
+    impl AtomicWaker {
+        /// Create a new waker, with no task registered
+        #[inline]
+        pub const fn new() -> AtomicWaker {
+            AtomicWaker(Mutex::new(RefCell::new(None)))
+        }
+
+        /// Register the waker of a task, replacing the previous one
+        #[inline]
+        pub fn register(&self, waker: &Waker) {
+            critical_section::with(|cs| {
+                let mut slot = self.0.borrow_ref_mut(cs);
+                match &*slot {
+                    Some(old) if old.will_wake(waker) => {}
+                    _ => *slot = Some(waker.clone()),
+                }
+            })
+        }
+
+        /// Wake the registered task, if any
+        #[inline]
+        pub fn wake(&self) {
+            if let Some(waker) = critical_section::with(|cs| self.0.borrow_ref_mut(cs).take()) {
+                waker.wake();
+            }
+        }
+    }
+
+    impl Default for AtomicWaker {
+        #[inline]
+        fn default() -> AtomicWaker {
+            AtomicWaker::new()
+        }
+    }
+
+    impl core::fmt::Debug for AtomicWaker {
+        #[inline]
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("AtomicWaker")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::example::periph::{StatFlag, STATUS};
+    use crate::utils::mock::{self, poll, Counter, Mock, PERIPH};
+    use core::pin::pin;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::{Context, Poll};
+    use std::sync::Arc;
+
+    impl WakerRegistry for PERIPH {}
+
+    /// A peripheral waking tasks from its interrupt handler
+    #[allow(clippy::upper_case_acronyms)]
+    enum IRQ {}
+
+    impl Peripheral for IRQ {
+        const BASE: usize = 0x4002_0000;
+        const NAME: &'static str = "IRQ";
+    }
+
+    impl WakerRegistry for IRQ {
+        fn register(waker: &Waker) {
+            IRQ_WAKER.register(waker);
+        }
+    }
+
+    static IRQ_WAKER: AtomicWaker = AtomicWaker::new();
+
+    #[test]
+    fn wait_for_poll() {
+        let status = unsafe { Reg::<STATUS, Mock<PERIPH>>::steal() };
+        let mut reads = 0;
+        mock::on_read::<STATUS, PERIPH, _>(move |value| {
+            reads += 1;
+            value | StatFlag(reads >= 3)
+        });
+
+        let mut future = pin!(status.wait_for(StatFlag(true)));
+        assert_eq!(poll(future.as_mut()), (Poll::Pending, 1));
+        assert_eq!(poll(future.as_mut()), (Poll::Pending, 1));
+        match poll(future.as_mut()) {
+            (Poll::Ready(value), _) => assert_eq!(value.value(), 0x0004),
+            (Poll::Pending, _) => ::core::panic!("still pending"),
+        }
+        assert_eq!(mock::take_log().len(), 3);
+    }
+
+    #[test]
+    fn wait_for_waker() {
+        let status = unsafe { Reg::<STATUS, Mock<IRQ>>::steal() };
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut future = pin!(status.wait_for(StatFlag(true)));
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+        mock::set::<STATUS, IRQ>(0x0004);
+        IRQ_WAKER.wake();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+    }
+}